@@ -1,64 +1,199 @@
-from typing import Optional, List, Union
+import re
+from typing import AnyStr, Callable, Dict, Generic, Iterable, Iterator, Optional, List, Tuple, Union, overload
+
+# `re`'s integer flags, accepted wherever a `config` is. LOCALE is not
+# supported and raises ValueError.
+NOFLAG: int
+I: int
+IGNORECASE: int
+L: int
+LOCALE: int
+M: int
+MULTILINE: int
+S: int
+DOTALL: int
+U: int
+UNICODE: int
+X: int
+VERBOSE: int
+A: int
+ASCII: int
+
+class error(re.error):
+    """
+    Raised when a pattern cannot be compiled. Subclasses `re.error`, so
+    `msg`, `pattern`, `pos`, `lineno` and `colno` are available; `pos`
+    counts characters of `pattern` and is None when the engine gave no
+    location.
+
+    When every engine rejects the pattern, the error leads with PCRE2's
+    reason and `engine_errors` lists each engine's, also attached as notes.
+    """
+    engine_errors: List[Tuple[str, str]]
+
+PatternError = error
+
+class TemplateError(error):
+    """
+    Raised for an invalid replacement template in `sub`, `subn` and
+    `sub_many`; `pattern` and `pos` refer to the template.
+    """
+
+class MatchLimitError(RuntimeError):
+    """
+    Raised when an engine gives up on a search, e.g. when PCRE2 or fancy-regex
+    hits its `backtrack_limit`, rather than reporting no match.
+    """
 
 class SelectEngine:
     Std: int
     Pcre2: int
     Fancy: int
 
-class Match:
+class Syntax:
+    """
+    How a pattern is read (see `ReConfig.syntax`).
+
+    Python: the syntax of Python's `re`, translated into each engine's dialect (the default).
+    Native: the pattern is handed to the engine as is, in its own dialect.
+    """
+    Python: int
+    Native: int
+
+class MatchErrorPolicy:
+    """
+    What a search does when the engine gives up on it (see `ReConfig.on_match_error`).
+
+    Raise: raise `MatchLimitError` (the default).
+    NoMatch: report no match.
+    Fallback: retry on the other backtracking engine (PCRE2 or fancy-regex),
+              raising `MatchLimitError` if it cannot run the pattern or fails too.
+    """
+    Raise: int
+    NoMatch: int
+    Fallback: int
+
+class Match(Generic[AnyStr]):
     """
     Represents a successful regex match.
     contains the original text and span indices for the match and capture groups.
     """
-    def start(self) -> int:
+    string: AnyStr
+    """The string passed to `match()`, `search()` or `finditer()`."""
+    re: "Pattern"
+    """The Pattern object that produced this match."""
+    pos: int
+    """The index where the regex engine started looking for a match."""
+    endpos: int
+    """The index beyond which the regex engine did not look."""
+    lastindex: Optional[int]
+    """The index of the last matched capturing group, or None if no group matched."""
+    lastgroup: Optional[str]
+    """The name of the last matched capturing group, or None if it has no name or no group matched."""
+
+    def start(self, ident: Union[int, str] = 0) -> int:
+        """
+        Returns the starting index of the given group, or -1 if it did not participate.
+
+        Indices count characters (code points) like Python's `re`, so
+        `text[m.start():m.end()]` is always the matched substring.
+        """
+
+    def end(self, ident: Union[int, str] = 0) -> int:
         """
-        Returns the starting index of the match.
+        Returns the ending index of the given group, or -1 if it did not participate.
         """
 
-    def end(self) -> int:
+    def span(self, ident: Union[int, str] = 0) -> Tuple[int, int]:
         """
-        Returns the ending index of the match.
+        Returns the (start, end) indices of the given group, or (-1, -1) if it did not participate.
         """
 
-    def group(self, ident: Union[int, str] = 0) -> str:
+    @overload
+    def group(self, ident: Union[int, str] = 0, /) -> Optional[AnyStr]: ...
+    @overload
+    def group(self, ident: Union[int, str], *idents: Union[int, str]) -> Tuple[Optional[AnyStr], ...]: ...
+    def group(self, *idents: Union[int, str]) -> Union[Optional[AnyStr], Tuple[Optional[AnyStr], ...]]:
         """
-        Returns the substring matched by the given group.
+        Returns the substring matched by the given group(s).
 
         Args:
-            ident: The group index (int) or group name (str). Defaults to 0 (the whole match).
+            idents: Group indexes (int) or group names (str). Defaults to 0 (the whole match).
 
         Returns:
-            The matched string.
+            The matched string, or None if the group did not participate in the match.
+            With several arguments, a tuple holding one result per argument.
 
         Raises:
-            ValueError: If the group index/name is invalid or not found.
+            IndexError: If the group index/name is not defined in the pattern.
+        """
+
+    def __getitem__(self, ident: Union[int, str]) -> Optional[AnyStr]:
+        """
+        Equivalent to `m.group(ident)`.
         """
 
-    def groups(self) -> List[Optional[str]]:
+    def groups(self, default: Optional[AnyStr] = None) -> Tuple[Optional[AnyStr], ...]:
         """
-        Returns a list of all capture groups (excluding the specific whole-match group 0).
+        Returns a tuple of all capture groups (excluding the specific whole-match group 0).
+
+        Args:
+            default: Value reported for groups that did not participate in the match.
 
         Returns:
-            A list where each element is the string matched by the group, or None if the group did not participate.
+            A tuple where each element is the string matched by the group, or `default` if the group did not participate.
         """
 
-    def lastindex(self) -> int:
+    def groupdict(self, default: Optional[AnyStr] = None) -> Dict[str, Optional[AnyStr]]:
         """
-        Returns the integer index of the last matched capturing group.
+        Returns a dictionary mapping every named group to the substring it matched.
+
+        Args:
+            default: Value reported for groups that did not participate in the match.
         """
 
-class Pattern:
+class MatchIterator(Iterator[Match[AnyStr]]):
+    """
+    Lazily yields Match objects for every non-overlapping match, like `re.finditer`.
+    """
+    def __iter__(self) -> "MatchIterator[AnyStr]": ...
+    def __next__(self) -> Match[AnyStr]: ...
+
+class Pattern(Generic[AnyStr]):
     """
     A compiled regular expression object.
+
+    Every search method accepts optional `pos` and `endpos` character indices,
+    with the same meaning as in Python's `re`: the scan starts at `pos` (while
+    look-behinds and `^` still see the text before it) and the string is treated
+    as if it ended at `endpos`.
+
+    A pattern compiled from `bytes` searches `bytes`, `bytearray` or `memoryview`
+    subjects in place and returns `bytes`; indices then count bytes. Mixing a
+    `str` pattern with a bytes-like subject (or the reverse) raises TypeError.
     
     This object holds a thread-safe reference to the underlying Rust Regex engine.
     It automatically handles switching between the standard `regex` crate (O(n) time)
     and `fancy-regex` (for look-arounds and back-references) based on the pattern.
     """
 
-    def engine_info(self) -> str:
+    def engine_info(self, verbose: bool = False) -> str:
+        """
+        Returns the name of the underlying engine being used ('regex', 'fancy_regex', 'pcre2',
+        or 'aho_corasick' for alternations of plain literals).
+
+        With `verbose`, the name is followed by why the engine was picked, e.g.
+        "fancy_regex (needs a backtracking engine for backreferences)".
+        """
+
+    def features(self) -> List[str]:
         """
-        Returns the name of the underlying engine being used ('regex', 'fancy_regex', or 'pcre2').
+        The constructs of the pattern that call for a backtracking engine, among
+        'lookaround', 'backrefs', 'atomic' (atomic groups and possessive repeats),
+        'recursion', 'conditionals', 'final_newline' (Python's `$`, which also
+        matches before a newline ending the text, anywhere but at the end of the
+        pattern) and 'word_boundary' (`\b` or `\B` outside `(?a)` in a `str`
+        pattern). Empty for patterns the linear-time `regex` crate runs.
         """
 
     def group_names(self) -> List[str]:
@@ -66,7 +201,7 @@ class Pattern:
         Returns a list of named capture groups defined in the pattern.
         """
 
-    def is_match(self, text: str) -> bool:
+    def is_match(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> bool:
         """
         Checks if the pattern matches the string at the beginning.
 
@@ -75,7 +210,7 @@ class Pattern:
         Returns:
             True if the pattern matches at the start of `text`.
         """
-    def is_search(self, text: str) -> bool:
+    def is_search(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> bool:
         """
         Checks if the pattern matches anywhere in the string.
 
@@ -85,51 +220,166 @@ class Pattern:
             True if the pattern is found anywhere in `text`.
         """
 
-    def match(self, text: str) -> Optional[Match]:
+    def match(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> Optional[Match[AnyStr]]:
         """
         Attempts to match the pattern at the beginning of the string.
 
         Returns:
             A Match object if found, otherwise None.
         """
-    def search(self, text: str) -> Optional[Match]:
+    def search(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> Optional[Match[AnyStr]]:
         """
         Searches for the pattern anywhere in the string.
 
         Returns:
             A Match object if found, otherwise None.
         """
-    def find(self, text: str) -> Optional[Match]:
+    def fullmatch(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> Optional[Match[AnyStr]]:
+        """
+        Attempts to match the pattern against the whole string.
+
+        The end anchor is the end of the text even in multiline mode.
+
+        Returns:
+            A Match object if the entire string matches, otherwise None.
+        """
+    def is_fullmatch(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> bool:
+        """
+        Checks if the pattern matches the whole string, without allocating a Match object.
+        """
+    def find(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> Optional[Match[AnyStr]]:
         """
         Finds the first occurrence of the pattern in the string.
 
         Returns:
             A Match object if found, otherwise None.
         """
-    def findall(self, text: str) -> List[str]:
+    def find_indices(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> Optional[Tuple[int, int]]:
+        """
+        Returns the (start, end) character indices of the first match, or None.
+        """
+    def findall(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> List[AnyStr]:
         """
         Finds all non-overlapping occurrences of the pattern in the string.
         """
+    def finditer(self, text: AnyStr, pos: Optional[int] = None, endpos: Optional[int] = None) -> MatchIterator[AnyStr]:
+        """
+        Returns an iterator yielding a Match object for every non-overlapping match.
 
-    def sub(self, repl: str, text: str) -> str:
+        Matches are produced one at a time, so the whole result list is never built up front.
+
+        Args:
+            text: The input string to scan.
+            pos: Index where the scan starts. Defaults to the start of the string.
+            endpos: Index where the scan stops, as if the string ended there.
+        """
+
+    def sub(self, repl: Union[AnyStr, Callable[[Match[AnyStr]], AnyStr]], text: AnyStr, count: int = 0, *, rust_syntax: bool = False) -> AnyStr:
         """
         Return the string obtained by replacing the leftmost non-overlapping occurrences
         of the pattern in string by the replacement `repl`.
 
         Args:
-            repl: The replacement string.
+            repl: The replacement string, using Python's `\1` / `\g<name>` syntax,
+                or a callable receiving each Match and returning its replacement.
             text: The input string to perform replacements on.
+            count: Maximum number of replacements; 0 replaces every occurrence.
+            rust_syntax: Interpret `repl` with the Rust `$1` / `${name}` syntax instead.
         Returns:
             The modified string with replacements.
         """
 
+    def subn(self, repl: Union[AnyStr, Callable[[Match[AnyStr]], AnyStr]], text: AnyStr, count: int = 0, *, rust_syntax: bool = False) -> Tuple[AnyStr, int]:
+        """
+        Perform the same operation as `sub()`, but return a tuple (new_string, number_of_subs_made).
+        """
+
+    def split(self, text: AnyStr, maxsplit: int = 0) -> List[Optional[AnyStr]]:
+        """
+        Split the string by the occurrences of the pattern, like `re.split`.
+
+        If the pattern has capturing groups, the text of every group is also
+        returned between the pieces (None for groups that did not participate).
+
+        Args:
+            text: The input string to split.
+            maxsplit: Maximum number of splits; 0 splits at every occurrence.
+        """
+
+    def is_search_many(self, texts: Iterable[AnyStr], n_threads: Optional[int] = None) -> List[bool]:
+        """
+        Runs `is_search` on every string of `texts`, in parallel and with the GIL released.
+
+        The batch variants cross into Rust once for the whole batch, which pays
+        off for many short strings. Results are returned in input order.
+
+        Args:
+            texts: The input strings.
+            n_threads: Maximum number of worker threads; defaults to, and is capped
+                       at, one per core.
+        """
+    def search_many(self, texts: Iterable[AnyStr], n_threads: Optional[int] = None) -> List[Optional[Match[AnyStr]]]:
+        """
+        Runs `search` on every string of `texts` in parallel, see `is_search_many`.
+        """
+    def findall_many(self, texts: Iterable[AnyStr], n_threads: Optional[int] = None) -> List[List[AnyStr]]:
+        """
+        Runs `findall` on every string of `texts` in parallel, see `is_search_many`.
+        """
+    def sub_many(
+        self,
+        repl: Union[AnyStr, Callable[[Match[AnyStr]], AnyStr]],
+        texts: Iterable[AnyStr],
+        count: int = 0,
+        n_threads: Optional[int] = None,
+        *,
+        rust_syntax: bool = False,
+    ) -> List[AnyStr]:
+        """
+        Runs `sub` on every string of `texts` in parallel, see `is_search_many`.
+
+        A callable `repl` needs the GIL for each match, so the strings are then
+        processed one after another.
+        """
+
     @staticmethod
-    def escape(text: str) -> str:
+    def escape(text: AnyStr) -> AnyStr:
         """
         Escape special characters in a string.
         """
 
 
+class PatternSet(Generic[AnyStr]):
+    """
+    Several patterns tested against a text in one pass.
+
+    Patterns the standard `regex` engine accepts share a single `RegexSet`
+    automaton; the others (look-arounds, back-references, ...) run on their
+    own PCRE2 or fancy-regex engine. Sets are cached like `compile`.
+    """
+    def __init__(self, patterns: Iterable[AnyStr], config: Optional[Union["ReConfig", int]] = None) -> None: ...
+    def __len__(self) -> int: ...
+    def engine_info(self) -> str:
+        """
+        Names the engines backing the set, e.g. 'regex_set+pcre2'.
+        """
+    def matches(self, text: AnyStr) -> List[int]:
+        """
+        Returns the indices of every pattern found in `text`, in ascending order.
+        """
+    def is_search(self, text: AnyStr) -> bool:
+        """
+        Checks if any of the patterns is found in `text`.
+        """
+    def first_match(self, text: AnyStr) -> Optional[int]:
+        """
+        Returns the lowest index of a pattern found in `text`, or None.
+        """
+    def matches_many(self, texts: Iterable[AnyStr], n_threads: Optional[int] = None) -> List[List[int]]:
+        """
+        Runs `matches` on every string of `texts`, in parallel and with the GIL released.
+        """
+
 class ReConfig:
     """
     Configuration options for compiling a regex pattern.
@@ -141,6 +391,13 @@ class ReConfig:
     size_limit: Optional[int]
     dfa_size_limit: int
     backtrack_limit: Optional[int]
+    on_match_error: MatchErrorPolicy
+    syntax: Syntax
+    dotall: bool
+    swap_greed: bool
+    crlf: bool
+    ascii: bool
+    jit: bool
 
     def __init__(self, 
         case_insensitive: bool = False,
@@ -149,51 +406,95 @@ class ReConfig:
         unicode_mode: bool = False,
         size_limit: Optional[int] = None,
         dfa_size_limit: int = 10_000_000,
-        backtrack_limit: Optional[int] = None
+        backtrack_limit: Optional[int] = None,
+        on_match_error: MatchErrorPolicy = MatchErrorPolicy.Raise,
+        syntax: Syntax = Syntax.Python,
+        dotall: bool = False,
+        swap_greed: bool = False,
+        crlf: bool = False,
+        ascii: bool = False,
+        jit: bool = True
     ) -> None:
         """
         Args:
             case_insensitive: Enable case-insensitive matching.
             ignore_whitespace: Allow whitespace and comments in pattern.
             multiline: ^ and $ match start/end of line.
-            unicode_mode: Enable Unicode support.
-            size_limit: Limit the size of the compiled regex.
-            dfa_size_limit: Limit the size of the DFA graph (std engine only).
-            backtrack_limit: Limit the backtrack stack (fancy engine only).
-        """
-
-
-def compile(pattern: str, config: Optional[ReConfig] = None) -> Pattern:
+            unicode_mode: Enable Unicode support (`Syntax.Native` only; Python
+                          syntax is always Unicode for `str` patterns).
+            size_limit: Limit the size of the compiled regex; on PCRE2, the
+                        heap memory a search may use.
+            dfa_size_limit: Limit the size of the DFA graph; on PCRE2, the
+                            JIT stack size.
+            backtrack_limit: Limit the backtracking of fancy-regex and PCRE2
+                             (its match and depth limits).
+            on_match_error: What a search does when the engine gives up on it.
+            syntax: Whether the pattern is Python `re` syntax or the engine's own.
+            dotall: `.` also matches a newline, like `re.DOTALL`.
+            swap_greed: Repeats are lazy unless followed by `?`.
+            crlf: `\r`, `\n` and `\r\n` all end a line for `^`, `$` and `.`
+                  (not supported by fancy-regex with `Syntax.Native`).
+            ascii: `\w`, `\d`, `\s` and `\b` only match ASCII, like `re.ASCII`.
+            jit: JIT-compile PCRE2 patterns where the platform supports it.
+
+        Instead of a ReConfig, functions taking a `config` also accept `re`-style
+        integer flags such as `reru.I | reru.M`.
+        """
+
+
+def compile(pattern: AnyStr, config: Optional[Union[ReConfig, int]] = None, *, cache: bool = True) -> Pattern[AnyStr]:
     """
     Compile a regular expression pattern into a Pattern object.
 
     This function utilizes a thread-safe cache. If the pattern (and config) 
     has been seen before, a cached Pattern is returned immediately.
 
+    A `bytes` pattern builds byte-oriented engines (never fancy-regex): as in
+    Python's `re`, classes like `\w` are ASCII-only and `.` matches any byte
+    but a newline.
+
     Args:
-        pattern: The regex string.
+        pattern: The regex string or bytes.
         config: Optional configuration object.
+        cache: Set to False to compile afresh, leaving the cache untouched.
 
     Returns:
         A compiled Pattern object.
     """
 
 def compile_custom(
-    pattern: str,
-    config: Optional[ReConfig] = None,
-    select_engine: Optional[SelectEngine] = None
-) -> Pattern:
+    pattern: AnyStr,
+    config: Optional[Union[ReConfig, int]] = None,
+    select_engine: Optional[SelectEngine] = None,
+    *,
+    cache: bool = True
+) -> Pattern[AnyStr]:
     """
-    Compile a regex pattern, forcing a specific underlying engine.
+    Compile a regex pattern, forcing a specific underlying engine. Patterns
+    share `compile`'s cache, keyed by the selected engine as well.
 
     Args:
         pattern: The regex string.
         config: Optional configuration.
         select_engine: Force usage of 'Std' (Rust Regex) or 'Fancy' (FancyRegex).
                        If None, auto-detection is used.
+        cache: Set to False to compile afresh, leaving the cache untouched.
+    """
+
+def explain_compile(pattern: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> List[Tuple[str, bool, str]]:
+    """
+    Report how each engine handles a pattern: aho_corasick, regex, pcre2 and
+    fancy_regex. Every engine is built, whereas `compile` only builds the one
+    the pattern's features call for (see `Pattern.engine_info(verbose=True)`).
+
+    Returns:
+        One (engine, accepted, reason) tuple per engine. `reason` is the
+        engine's error when it is rejected. A pattern that is not valid Python
+        syntax is led by ('python_syntax', False, error), and every engine is
+        reported as not tried.
     """
 
-def is_match(pattern: str, text: str, config: Optional[ReConfig] = None) -> bool:
+def is_match(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> bool:
     """
     Checks if the pattern matches the string at the beginning.
 
@@ -206,7 +507,7 @@ def is_match(pattern: str, text: str, config: Optional[ReConfig] = None) -> bool
     Returns:
         True if the pattern matches at the start of `text`.
     """
-def is_search(pattern: str, text: str, config: Optional[ReConfig] = None) -> bool: 
+def is_search(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> bool: 
     """
     Checks if the pattern matches anywhere in the string.
 
@@ -220,7 +521,7 @@ def is_search(pattern: str, text: str, config: Optional[ReConfig] = None) -> boo
         True if the pattern is found anywhere in `text`.
     """
 
-def match(pattern: str, text: str, config: Optional[ReConfig] = None) -> Optional[Match]:
+def match(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> Optional[Match[AnyStr]]:
     """
     Attempts to match the pattern at the beginning of the string.
 
@@ -232,7 +533,29 @@ def match(pattern: str, text: str, config: Optional[ReConfig] = None) -> Optiona
         A Match object if found, otherwise None.
     """
 
-def search(pattern: str, text: str, config: Optional[ReConfig] = None) -> Optional[Match]:
+def fullmatch(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> Optional[Match[AnyStr]]:
+    """
+    Attempts to match the pattern against the whole string.
+
+    Args:
+        pattern: The regex string.
+        text: The input string to match against.
+        config: Optional configuration.
+    Returns:
+        A Match object if the entire string matches, otherwise None.
+    """
+
+def is_fullmatch(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> bool:
+    """
+    Checks if the pattern matches the whole string, without allocating a Match object.
+
+    Args:
+        pattern: The regex string.
+        text: The input string to match against.
+        config: Optional configuration.
+    """
+
+def search(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> Optional[Match[AnyStr]]:
     """
     Searches for the pattern anywhere in the string.
 
@@ -244,26 +567,126 @@ def search(pattern: str, text: str, config: Optional[ReConfig] = None) -> Option
         A Match object if found, otherwise None.
     """
 
-def sub(pattern: str, repl: str, text: str, config: Optional[ReConfig] = None) -> str:
+def finditer(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None) -> MatchIterator[AnyStr]:
+    """
+    Returns an iterator yielding a Match object for every non-overlapping match.
+
+    Args:
+        pattern: The regex string.
+        text: The input string to scan.
+        config: Optional configuration.
+    Returns:
+        A lazy iterator of Match objects.
+    """
+
+def sub(
+    pattern: AnyStr,
+    repl: Union[AnyStr, Callable[[Match[AnyStr]], AnyStr]],
+    text: AnyStr,
+    config: Optional[Union[ReConfig, int]] = None,
+    count: int = 0,
+    *,
+    rust_syntax: bool = False,
+) -> AnyStr:
     """
     Return the string obtained by replacing the leftmost non-overlapping occurrences
     of the pattern in string by the replacement `repl`.
 
     Args:
         pattern: The regex string.
-        repl: The replacement string.
+        repl: The replacement string, using Python's `\1` / `\g<name>` syntax,
+            or a callable receiving each Match and returning its replacement.
         text: The input string to perform replacements on.
         config: Optional configuration.
+        count: Maximum number of replacements; 0 replaces every occurrence.
+        rust_syntax: Interpret `repl` with the Rust `$1` / `${name}` syntax instead.
     Returns:
         The modified string with replacements.
     """
 
-def escape(text: str) -> str:
+def subn(
+    pattern: AnyStr,
+    repl: Union[AnyStr, Callable[[Match[AnyStr]], AnyStr]],
+    text: AnyStr,
+    config: Optional[Union[ReConfig, int]] = None,
+    count: int = 0,
+    *,
+    rust_syntax: bool = False,
+) -> Tuple[AnyStr, int]:
+    """
+    Perform the same operation as `sub()`, but return a tuple (new_string, number_of_subs_made).
+    """
+
+def split(pattern: AnyStr, text: AnyStr, config: Optional[Union[ReConfig, int]] = None, maxsplit: int = 0) -> List[Optional[AnyStr]]:
+    """
+    Split the string by the occurrences of the pattern, like `re.split`.
+
+    Args:
+        pattern: The regex string.
+        text: The input string to split.
+        config: Optional configuration.
+        maxsplit: Maximum number of splits; 0 splits at every occurrence.
+    """
+
+def escape(text: AnyStr) -> AnyStr:
     """
     Escape special characters in a string.
     """
 
 
+def set_gil_release_threshold(size: int) -> None:
+    """
+    Set the subject length from which searches release the GIL.
+
+    Scans of subjects at least `size` long (characters for `str`, bytes
+    otherwise) run without holding the GIL, so other Python threads keep
+    running. Mutable buffers such as `bytearray` always keep the GIL.
+    0 releases it for every search; the default is 65536.
+    """
+
+def get_gil_release_threshold() -> int:
+    """
+    Return the subject length from which searches release the GIL.
+    """
+
+class CacheInfo:
+    """Statistics of the compile cache, returned by `cache_info()`. Pattern sets are not counted."""
+    hits: int
+    misses: int
+    evictions: int
+    currsize: int
+    maxsize: int
+    memory: int
+    """
+    Approximate bytes held by the cached patterns. PCRE2 and fancy-regex do
+    not report their size, so theirs is estimated from the pattern's length.
+    """
+
+def set_cache_size(size: int) -> None:
+    """
+    Set how many compiled patterns are cached, and separately how many
+    pattern sets. The least recently used ones beyond it are evicted.
+    0 disables caching; the default is 512.
+    """
+
+def get_cache_size() -> int:
+    """
+    Return how many compiled patterns are cached.
+    """
+
+def purge() -> None:
+    """
+    Clear the caches of compiled patterns and pattern sets, like `re.purge()`,
+    and reset the counters of `cache_info()`.
+    """
+
+def cache_info() -> CacheInfo:
+    """
+    Return hits, misses, evictions, size and approximate memory of the
+    compile cache. The cache of `PatternSet`s is not counted.
+    """
+
+
 
 __version__: str
 __name__: str
@@ -0,0 +1,201 @@
+use crate::{syntax_parser, tiers, ReConfig, SelectEngine};
+use crate::syntax::{Greed, Group, Node};
+
+// Constructs the `regex` crate cannot run.
+const LOOKAROUND: u8 = 1;
+const BACKREFS: u8 = 2;
+const ATOMIC: u8 = 4;
+const RECURSION: u8 = 8;
+const CONDITIONALS: u8 = 16;
+/// Python's `$`, which also matches before a newline ending the text.
+const FINAL_NEWLINE: u8 = 32;
+/// Python's `\b` and `\B` on text, whose `\w` differs from the `regex` crate's.
+const WORD_BOUNDARY: u8 = 64;
+
+/// Each feature with its name in `Pattern.features()` and in reasons.
+const NAMES: [(u8, &str, &str); 7] = [
+    (LOOKAROUND, "lookaround", "look-around"),
+    (BACKREFS, "backrefs", "backreferences"),
+    (ATOMIC, "atomic", "atomic groups"),
+    (RECURSION, "recursion", "recursion"),
+    (CONDITIONALS, "conditionals", "conditionals"),
+    (FINAL_NEWLINE, "final_newline", "`$` before a final newline"),
+    (WORD_BOUNDARY, "word_boundary", "Unicode word boundaries"),
+];
+
+/// The constructs of a pattern that only backtracking engines can run.
+#[derive(Debug, Clone, Default)]
+pub struct Features {
+    found: u8,
+    /// Why the `regex` crate's parser rejects a native pattern, if it does.
+    rejected: Option<String>,
+}
+
+impl Features {
+    /// Walks a parsed Python pattern. A `$` ending it only counts under `crlf`,
+    /// as the `regex` crate runs it otherwise (see `Parsed::emit`).
+    pub fn of_python(root: &Node, crlf: bool, bytes: bool) -> Features {
+        fn walk(node: &Node, found: &mut u8, bytes: bool) {
+            match node {
+                Node::End { multiline: false } => *found |= FINAL_NEWLINE,
+                Node::WordBoundary { ascii: false, .. } if !bytes => *found |= WORD_BOUNDARY,
+                Node::Backref(_) => *found |= BACKREFS,
+                Node::Group { kind, body } => {
+                    *found |= match kind {
+                        Group::LookAhead { .. } | Group::LookBehind { .. } => LOOKAROUND,
+                        Group::Atomic => ATOMIC,
+                        _ => 0,
+                    };
+                    walk(body, found, bytes);
+                },
+                Node::Repeat { body, greed, .. } => {
+                    if *greed == Greed::Possessive {
+                        *found |= ATOMIC;
+                    }
+                    walk(body, found, bytes);
+                },
+                Node::Conditional { yes, no, .. } => {
+                    *found |= CONDITIONALS;
+                    walk(yes, found, bytes);
+                    if let Some(no) = no {
+                        walk(no, found, bytes);
+                    }
+                },
+                Node::Concat(nodes) | Node::Alternation(nodes) => nodes.iter().for_each(|node| walk(node, found, bytes)),
+                _ => {},
+            }
+        }
+        let mut found = 0;
+        match root.before_final_end() {
+            Some(rest) if !crlf => rest.iter().for_each(|node| walk(node, &mut found, bytes)),
+            _ => walk(root, &mut found, bytes),
+        }
+        Features { found, rejected: None }
+    }
+
+    /// Checks a native pattern with `regex-syntax`, set up like the `regex`
+    /// crate would parse it, and scans the patterns it rejects for extensions.
+    pub fn of_native(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Features {
+        match syntax_parser(config, bytes).parse(pattern) {
+            Ok(_) => Features::default(),
+            Err(e) => {
+                let rejected = match &e {
+                    regex_syntax::Error::Parse(e) => e.kind().to_string(),
+                    regex_syntax::Error::Translate(e) => e.kind().to_string(),
+                    e => e.to_string(),
+                };
+                Features { found: scan_native(pattern), rejected: Some(rejected) }
+            },
+        }
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        NAMES.iter().filter(|(bit, ..)| self.found & bit != 0).map(|(_, name, _)| *name).collect()
+    }
+
+    fn describe(&self) -> String {
+        NAMES.iter().filter(|(bit, ..)| self.found & bit != 0).map(|(.., label)| *label).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Finds the extensions in a pattern written for PCRE2 or fancy-regex. It only
+/// tells constructs apart: checking the pattern is left to the engines.
+fn scan_native(pattern: &str) -> u8 {
+    let chars: Vec<char> = pattern.chars().collect();
+    let at = |i: usize| chars.get(i).copied();
+    let mut found = 0;
+    let mut in_class = false;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                match at(i + 1) {
+                    // `\Q...\E` quotes its text.
+                    Some('Q') => {
+                        i += 2;
+                        while i < chars.len() && !(chars[i] == '\\' && at(i + 1) == Some('E')) {
+                            i += 1;
+                        }
+                    },
+                    _ if in_class => {},
+                    Some('1'..='9' | 'k') => found |= BACKREFS,
+                    // `\g<name>` and `\g'name'` call a group, `\g{1}` and `\g1` refer to one.
+                    Some('g') => found |= match at(i + 2) {
+                        Some('<' | '\'') => RECURSION,
+                        _ => BACKREFS,
+                    },
+                    _ => {},
+                }
+                i += 2;
+                continue;
+            },
+            '[' if !in_class => {
+                in_class = true;
+                if at(i + 1) == Some('^') {
+                    i += 1;
+                }
+                // A `]` opening the set is a literal.
+                if at(i + 1) == Some(']') {
+                    i += 1;
+                }
+            },
+            ']' if in_class => in_class = false,
+            _ if in_class => {},
+            '(' if at(i + 1) == Some('?') => {
+                let ext: String = chars[i + 2..].iter().take(2).collect();
+                found |= match (at(i + 2), at(i + 3)) {
+                    (Some('=' | '!'), _) | (Some('<'), Some('=' | '!')) => LOOKAROUND,
+                    (Some('>'), _) => ATOMIC,
+                    (Some('('), _) => CONDITIONALS,
+                    (Some('R' | '&' | '0'..='9'), _) | (Some('+' | '-'), Some('0'..='9')) => RECURSION,
+                    _ if ext == "P>" => RECURSION,
+                    _ if ext == "P=" => BACKREFS,
+                    _ => 0,
+                };
+                i += 2;
+                continue;
+            },
+            // A possessive repeat.
+            '*' | '+' | '?' | '}' if at(i + 1) == Some('+') => {
+                found |= ATOMIC;
+                i += 2;
+                continue;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    found
+}
+
+/// The engines that can run a pattern with `features`, best first, and why
+/// the first one is picked. Engines that lack a construct are never tried.
+pub fn choose(features: &Features, bytes: bool) -> (Vec<(&'static str, SelectEngine)>, String) {
+    let tiers = tiers(bytes);
+    if features.found == 0 && features.rejected.is_none() {
+        return (tiers.to_vec(), "no backtracking features, so it runs in linear time".to_string());
+    }
+    let mut backtracking: Vec<_> = tiers.iter().copied().filter(|(_, kind)| *kind != SelectEngine::Std).collect();
+    let reason = if features.found & RECURSION != 0 {
+        backtracking.retain(|(_, kind)| *kind == SelectEngine::Pcre2);
+        "recursion is only supported by PCRE2".to_string()
+    } else if features.found == 0 {
+        format!("the regex crate rejects it: {}", features.rejected.as_deref().unwrap_or_default())
+    } else {
+        // fancy-regex hands the parts of a pattern around its look-around and
+        // backreferences to the `regex` crate; atomic groups and conditionals
+        // stay on PCRE2's JIT.
+        if features.found & (ATOMIC | CONDITIONALS) == 0 {
+            backtracking.reverse();
+        }
+        format!("needs a backtracking engine for {}", features.describe())
+    };
+    (backtracking, reason)
+}
+
+/// Why a `Pattern` runs on its engine.
+#[derive(Debug)]
+pub struct Choice {
+    pub features: Features,
+    pub reason: String,
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use pyo3::prelude::*;
+
+/// How many entries each cache keeps; 0 disables caching.
+static CAPACITY: AtomicUsize = AtomicUsize::new(512);
+
+pub fn capacity() -> usize {
+    CAPACITY.load(Ordering::Relaxed)
+}
+
+pub fn set_capacity(capacity: usize) {
+    CAPACITY.store(capacity, Ordering::Relaxed);
+}
+
+struct Entry<V> {
+    value: V,
+    /// When the entry was last used; its key in `Inner::order`.
+    used: u64,
+    size: usize,
+}
+
+struct Inner<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// Keys from least to most recently used.
+    order: BTreeMap<u64, K>,
+    clock: u64,
+    memory: usize,
+}
+
+/// A thread-safe cache that evicts the least recently used entry once it
+/// holds `capacity()` entries.
+pub struct LruCache<K, V> {
+    inner: Mutex<Inner<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    pub fn new() -> Self {
+        LruCache {
+            inner: Mutex::new(Inner { entries: HashMap::new(), order: BTreeMap::new(), clock: 0, memory: 0 }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner<K, V>> {
+        // An entry is only ever changed whole, so a poisoned cache is still consistent.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut inner = self.lock();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let Some(entry) = inner.entries.get_mut(key) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let used = std::mem::replace(&mut entry.used, clock);
+        let value = entry.value.clone();
+        if let Some(key) = inner.order.remove(&used) {
+            inner.order.insert(clock, key);
+        }
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(value)
+    }
+
+    /// Caches `value`, which holds about `size` bytes, evicting the least
+    /// recently used entries to make room.
+    pub fn insert(&self, key: K, value: V, size: usize) {
+        let capacity = capacity();
+        let mut inner = self.lock();
+        if let Some(old) = inner.entries.remove(&key) {
+            inner.order.remove(&old.used);
+            inner.memory -= old.size;
+        }
+        self.evict(&mut inner, capacity.saturating_sub(1));
+        if capacity == 0 {
+            return;
+        }
+        inner.clock += 1;
+        let used = inner.clock;
+        inner.order.insert(used, key.clone());
+        inner.memory += size;
+        inner.entries.insert(key, Entry { value, used, size });
+    }
+
+    /// Evicts the least recently used entries beyond `capacity()`.
+    pub fn shrink(&self) {
+        self.evict(&mut self.lock(), capacity());
+    }
+
+    fn evict(&self, inner: &mut Inner<K, V>, keep: usize) {
+        while inner.entries.len() > keep {
+            let Some((_, oldest)) = inner.order.pop_first() else { break };
+            if let Some(old) = inner.entries.remove(&oldest) {
+                inner.memory -= old.size;
+                self.evictions.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Empties the cache and resets its statistics.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.entries.clear();
+        inner.order.clear();
+        inner.memory = 0;
+        for counter in [&self.hits, &self.misses, &self.evictions] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn info(&self) -> CacheInfo {
+        let inner = self.lock();
+        CacheInfo {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            currsize: inner.entries.len(),
+            maxsize: capacity(),
+            memory: inner.memory,
+        }
+    }
+}
+
+/// Statistics of the compile cache, like `functools.lru_cache`'s `cache_info()`.
+/// Pattern sets have a cache of their own, which is not counted.
+#[pyclass(frozen, get_all)]
+#[derive(Debug, Clone, Copy)]
+pub struct CacheInfo {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub currsize: usize,
+    pub maxsize: usize,
+    /// Approximate bytes held by the cached patterns.
+    pub memory: usize,
+}
+
+#[pymethods]
+impl CacheInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "CacheInfo(hits={}, misses={}, evictions={}, currsize={}, maxsize={}, memory={})",
+            self.hits, self.misses, self.evictions, self.currsize, self.maxsize, self.memory,
+        )
+    }
+}
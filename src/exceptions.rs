@@ -1,13 +1,28 @@
 use std::error::Error as StdError;
 
+use pyo3::prelude::*;
 use pyo3::{PyErr};
-use pyo3::exceptions::{PyException};
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyValueError};
+use pyo3::sync::PyOnceLock;
+use pyo3::types::PyType;
 use thiserror::Error;
 
+/// `reru.error`, raised for patterns no engine can compile.
+static ERROR: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+/// `reru.TemplateError`, raised for invalid replacement templates.
+static TEMPLATE_ERROR: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+/// `reru.MatchLimitError`, raised for searches an engine gave up on.
+static MATCH_LIMIT_ERROR: PyOnceLock<Py<PyType>> = PyOnceLock::new();
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub struct ReError {
     pub message: String,
+    /// The pattern (or replacement template) the error was found in.
+    pub pattern: Option<String>,
+    /// Where in `pattern` the error was found, in characters.
+    pub pos: Option<usize>,
+    /// Why each engine rejected the pattern, when they all did.
+    pub engine_errors: Vec<(&'static str, ReError)>,
 }
 
 impl std::fmt::Display for ReError {
@@ -16,6 +31,113 @@ impl std::fmt::Display for ReError {
     }
 }
 
+/// Character index of the byte `offset` of `pattern`.
+fn char_pos(pattern: &str, offset: usize) -> usize {
+    let mut offset = offset.min(pattern.len());
+    while !pattern.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    pattern[..offset].chars().count()
+}
+
+impl ReError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ReError { message: message.into(), pattern: None, pos: None, engine_errors: Vec::new() }
+    }
+
+    pub fn at(message: impl Into<String>, pattern: &str, pos: Option<usize>) -> Self {
+        ReError { message: message.into(), pattern: Some(pattern.to_string()), pos, engine_errors: Vec::new() }
+    }
+
+    /// Sums up the failures of every engine `create_engine` tried. PCRE2's
+    /// syntax is the closest to Python's, so its error leads when there is one.
+    pub fn all_failed(pattern: &str, engine_errors: Vec<(&'static str, ReError)>) -> Self {
+        let lead = engine_errors.iter()
+            .find(|(engine, _)| *engine == "pcre2")
+            .or(engine_errors.first())
+            .map(|(_, e)| (e.message.clone(), e.pos));
+        let (message, pos) = lead.unwrap_or_else(|| ("no engine could compile the pattern".to_string(), None));
+        ReError { message, pattern: Some(pattern.to_string()), pos, engine_errors }
+    }
+
+    /// The message with its position, the way `re.error` renders it.
+    pub fn report(&self) -> String {
+        match self.pos {
+            Some(pos) => format!("{} at position {}", self.message, pos),
+            None => self.message.clone(),
+        }
+    }
+
+    /// Describes a `regex` crate failure. `regex::Error` only carries a rendered
+    /// report, so the pattern is parsed again with `parser` to locate the error.
+    pub fn from_regex(pattern: &str, error: regex::Error, parser: &mut regex_syntax::Parser) -> Self {
+        match parser.parse(pattern) {
+            Err(regex_syntax::Error::Parse(e)) => ReError::at(e.kind().to_string(), pattern, Some(char_pos(pattern, e.span().start.offset))),
+            Err(regex_syntax::Error::Translate(e)) => ReError::at(e.kind().to_string(), pattern, Some(char_pos(pattern, e.span().start.offset))),
+            _ => ReError::at(error.to_string(), pattern, None),
+        }
+    }
+
+    /// Like `from_regex`, for the meta regex `std_engine` builds, reported the
+    /// way `regex::Regex` would.
+    pub fn from_meta(pattern: &str, error: regex_automata::meta::BuildError, parser: &mut regex_syntax::Parser) -> Self {
+        let error = match error.size_limit() {
+            Some(limit) => regex::Error::CompiledTooBig(limit),
+            None => regex::Error::Syntax(error.to_string()),
+        };
+        ReError::from_regex(pattern, error, parser)
+    }
+
+    /// Describes a PCRE2 failure for a `pattern` compiled behind a `prefix`
+    /// of inline flags.
+    pub fn from_pcre2(pattern: &str, prefix: &str, error: pcre2::Error) -> Self {
+        let report = error.to_string();
+        // "PCRE2: error compiling pattern at offset N: <message>"
+        let message = match error.kind() {
+            pcre2::ErrorKind::Compile => report.splitn(3, ": ").nth(2).unwrap_or(&report),
+            _ => &report,
+        };
+        let pos = error.offset().map(|offset| char_pos(pattern, offset.saturating_sub(prefix.len())));
+        ReError::at(message, pattern, pos)
+    }
+
+    /// Describes a fancy-regex failure for a `pattern` compiled behind a
+    /// `prefix` of inline flags.
+    pub fn from_fancy(pattern: &str, prefix: &str, error: fancy_regex::Error) -> Self {
+        match error {
+            fancy_regex::Error::ParseError(offset, e) => {
+                let pos = char_pos(pattern, offset.saturating_sub(prefix.len()));
+                ReError::at(e.to_string(), pattern, Some(pos))
+            },
+            e => ReError::at(e.to_string(), pattern, None),
+        }
+    }
+
+    fn into_pyerr(self, py: Python<'_>, kind: &PyOnceLock<Py<PyType>>) -> PyErr {
+        let Some(ty) = kind.get(py) else {
+            return PyValueError::new_err(self.message);
+        };
+        if self.engine_errors.is_empty() {
+            return PyErr::from_type(ty.bind(py).clone(), (self.message, self.pattern, self.pos));
+        }
+        let build = || -> PyResult<PyErr> {
+            let value = ty.bind(py).call1((self.message, self.pattern, self.pos))?;
+            let report: Vec<(&str, String)> = self.engine_errors.iter()
+                .map(|(engine, e)| (*engine, e.report()))
+                .collect();
+            // Notes show up in tracebacks from Python 3.11 on.
+            if value.hasattr("add_note")? {
+                for (engine, reason) in &report {
+                    value.call_method1("add_note", (format!("{}: {}", engine, reason),))?;
+                }
+            }
+            value.setattr("engine_errors", report)?;
+            Ok(PyErr::from_value(value))
+        };
+        build().unwrap_or_else(|e| e)
+    }
+}
+
 impl From<ReError> for AppError {
     fn from(error: ReError) -> Self {
         AppError::RegexError(error)
@@ -26,6 +148,7 @@ pub enum AppError{
     RegexError(ReError),
     InvalidPattern(ReError),
     IndexOutOfBounds(ReError),
+    MatchLimit(ReError),
 }
 
 
@@ -35,6 +158,7 @@ impl StdError for AppError {
             AppError::RegexError(e) => Some(e),
             AppError::InvalidPattern(e) => Some(e),
             AppError::IndexOutOfBounds(e) => Some(e),
+            AppError::MatchLimit(e) => Some(e),
         }
     }
 }
@@ -52,9 +176,10 @@ impl std::fmt::Display for AppError { // Error message for users.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use AppError::*;
         let message = match self {
-            RegexError(_) => "Failed to read the file.",
+            RegexError(msg) => &msg.message,
             InvalidPattern(msg) =>  &msg.message,
             IndexOutOfBounds(msg) => &msg.message,
+            MatchLimit(msg) => &msg.message,
         };
         write!(f, "{message}")
     }
@@ -62,8 +187,52 @@ impl std::fmt::Display for AppError { // Error message for users.
 
 impl From<AppError> for PyErr {
     fn from(error: AppError) -> Self {
-        PyException::new_err(format!("{error}"))
+        Python::attach(|py| match error {
+            AppError::RegexError(e) => e.into_pyerr(py, &ERROR),
+            AppError::InvalidPattern(e) => e.into_pyerr(py, &TEMPLATE_ERROR),
+            AppError::IndexOutOfBounds(e) => PyIndexError::new_err(e.message),
+            AppError::MatchLimit(e) => match MATCH_LIMIT_ERROR.get(py) {
+                Some(ty) => PyErr::from_type(ty.bind(py).clone(), e.message),
+                None => PyRuntimeError::new_err(e.message),
+            },
+        })
     }
 }
 
-
+/// Creates `reru.error` (also exported as `PatternError`), its `TemplateError`
+/// subclass and `MatchLimitError`. `reru.error` derives from `re.error`, whose
+/// constructor fills in `msg`, `pattern`, `pos`, `lineno` and `colno`, so
+/// handlers written for `re` keep catching it.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    let base = match py.import("re").and_then(|re| re.getattr("error")) {
+        Ok(base) => base.cast_into::<PyType>()?,
+        Err(_) => py.get_type::<PyValueError>(),
+    };
+    let error = ERROR.get_or_try_init(py, || PyErr::new_type(
+        py,
+        c"reru.error",
+        Some(c"Raised when a pattern cannot be compiled by any engine."),
+        Some(&base),
+        None,
+    ))?.bind(py);
+    let template_error = TEMPLATE_ERROR.get_or_try_init(py, || PyErr::new_type(
+        py,
+        c"reru.TemplateError",
+        Some(c"Raised for an invalid replacement template."),
+        Some(error),
+        None,
+    ))?.bind(py);
+    m.add("error", error)?;
+    m.add("PatternError", error)?;
+    let match_limit_error = MATCH_LIMIT_ERROR.get_or_try_init(py, || PyErr::new_type(
+        py,
+        c"reru.MatchLimitError",
+        Some(c"Raised when an engine gives up on a search, e.g. on hitting a backtracking limit."),
+        Some(&py.get_type::<PyRuntimeError>()),
+        None,
+    ))?.bind(py);
+    m.add("TemplateError", template_error)?;
+    m.add("MatchLimitError", match_limit_error)?;
+    Ok(())
+}
@@ -1,42 +1,327 @@
+use std::borrow::Cow;
 use std::hash::Hash;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
 use pyo3::{prelude::*};
-use pyo3::exceptions::PyValueError;
-use pyo3::types::PyString;
-use regex::{Regex, RegexBuilder};
+use pyo3::exceptions::{PyIndexError, PyRuntimeError, PyTypeError, PyValueError};
+use pyo3::buffer::PyBuffer;
+use pyo3::marker::Ungil;
+use pyo3::types::{PyBytes, PyDict, PyString, PyTuple};
+use regex_automata::{meta, Anchored, Input, MatchKind, PatternID};
+use regex_automata::util::syntax::Config as SyntaxConfig;
 use fancy_regex::{Regex as Regex2, RegexBuilder as RegexBuilder2};
 use pcre2::bytes::{Regex as Pcre2Regex, RegexBuilder as Pcre2RegexBuilder};
 use smallvec::{SmallVec,smallvec};
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use rayon::prelude::*;
+mod analysis;
+mod cache;
 mod exceptions;
+mod literals;
+mod pattern_set;
+mod syntax;
+mod template;
+mod unicode;
+use analysis::Choice;
+use cache::{CacheInfo, LruCache};
 use exceptions::AppError;
+use literals::Literals;
+use pattern_set::PatternSet;
+use syntax::{Anchor, Dialect, Source, Syntax};
+use template::Template;
 
 use crate::exceptions::ReError;
 
 
-type SpanVec = SmallVec<[(usize, usize); 8]>;
+/// Byte spans of group 0 and every capture group; `None` marks a group that
+/// did not participate in the match.
+pub(crate) type SpanVec = SmallVec<[Option<(usize, usize)>; 8]>;
+
+/// Views a haystack as `str` for the engines that only search text.
+///
+/// Text engines are only ever handed haystacks borrowed from a `str`: the
+/// public `ReEngine` methods take `&str` and `Pattern` refuses bytes-like
+/// subjects for `str` patterns (see `Pattern::subject`).
+#[inline]
+fn as_str(text: &[u8]) -> &str {
+    unsafe { std::str::from_utf8_unchecked(text) }
+}
+
+/// A known pair of byte offset and Python index in the subject.
+///
+/// The engines report byte offsets while Python indexes strings by code point,
+/// so positions are translated by counting characters from the closest anchor.
+#[derive(Debug, Clone, Copy, Default)]
+struct CharAnchor {
+    byte: usize,
+    chars: usize,
+}
+
+#[inline]
+fn count_chars(text: &str) -> usize {
+    if text.is_ascii() { text.len() } else { text.chars().count() }
+}
+
+impl CharAnchor {
+    #[inline]
+    fn to_char(self, text: Text, byte: usize) -> usize {
+        if byte >= self.byte {
+            self.chars + text.count(self.byte, byte)
+        } else {
+            text.count(0, byte)
+        }
+    }
+
+    /// Moves the anchor forward to `byte` so later translations start from there.
+    #[inline]
+    fn advance(&mut self, text: Text, byte: usize) {
+        if byte > self.byte {
+            self.chars = self.to_char(text, byte);
+            self.byte = byte;
+        }
+    }
+}
+
+/// Converts a code-point index into a byte offset, clamping to the end of `text`.
+#[inline]
+fn char_to_byte(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    if text.as_bytes()[..index].is_ascii() {
+        return index;
+    }
+    text.char_indices().nth(index).map(|(b, _)| b).unwrap_or(text.len())
+}
+
+/// The contents of a subject. Python indexes `str` by code point and
+/// bytes-like objects by byte, while the engines always work in bytes.
+#[derive(Clone, Copy)]
+enum Text<'t> {
+    Str(&'t str),
+    Bytes(&'t [u8]),
+}
+
+impl<'t> Text<'t> {
+    #[inline]
+    fn as_bytes(self) -> &'t [u8] {
+        match self {
+            Text::Str(s) => s.as_bytes(),
+            Text::Bytes(b) => b,
+        }
+    }
+
+    /// Python length of the bytes between offsets `start` and `end`.
+    #[inline]
+    fn count(self, start: usize, end: usize) -> usize {
+        match self {
+            Text::Str(s) => count_chars(&s[start..end]),
+            Text::Bytes(_) => end - start,
+        }
+    }
+
+    /// Converts a Python index into a byte offset, clamping to the end of the text.
+    #[inline]
+    fn to_byte(self, index: usize) -> usize {
+        match self {
+            Text::Str(s) => char_to_byte(s, index),
+            Text::Bytes(b) => index.min(b.len()),
+        }
+    }
+
+    /// The Python `str` or `bytes` holding the bytes between `start` and `end`.
+    ///
+    /// A bytearray may have shrunk since the spans were found, so bytes slices
+    /// are taken defensively.
+    fn slice<'py>(self, py: Python<'py>, start: usize, end: usize) -> Bound<'py, PyAny> {
+        match self {
+            Text::Str(s) => PyString::new(py, unsafe { s.get_unchecked(start..end) }).into_any(),
+            Text::Bytes(b) => PyBytes::new(py, b.get(start..end).unwrap_or_default()).into_any(),
+        }
+    }
+
+    /// Wraps `out`, built from this text and replacements of the same kind, as
+    /// a Python object of the subject's kind.
+    fn new_like<'py>(self, py: Python<'py>, out: &[u8]) -> Bound<'py, PyAny> {
+        match self {
+            Text::Str(_) => PyString::new(py, as_str(out)).into_any(),
+            Text::Bytes(_) => PyBytes::new(py, out).into_any(),
+        }
+    }
+}
+
+/// Subjects at least this long are searched with the GIL released.
+static GIL_RELEASE_THRESHOLD: AtomicUsize = AtomicUsize::new(64 * 1024);
+
+/// A subject passed from Python: a `str`, or any object exporting a byte
+/// buffer (`bytes`, `bytearray`, `memoryview`, ...), which is read in place.
+enum Subject<'py> {
+    Str(Bound<'py, PyString>),
+    Buffer(Bound<'py, PyAny>, PyBuffer<u8>),
+}
+
+impl<'py> Subject<'py> {
+    fn new(obj: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(s) = obj.cast::<PyString>() {
+            return Ok(Subject::Str(s.clone()));
+        }
+        match PyBuffer::<u8>::get(obj) {
+            Ok(buffer) if buffer.is_c_contiguous() => Ok(Subject::Buffer(obj.clone(), buffer)),
+            _ => Err(PyTypeError::new_err(format!(
+                "expected string or bytes-like object, got '{}'", obj.get_type().name()?
+            ))),
+        }
+    }
+
+    /// Reads the subject of a search with a `bytes` pattern (`bytes`) or a `str` one.
+    fn for_pattern(obj: &Bound<'py, PyAny>, bytes: bool) -> PyResult<Self> {
+        let subject = Subject::new(obj)?;
+        match (bytes, subject.is_bytes()) {
+            (false, true) => Err(PyTypeError::new_err("cannot use a string pattern on a bytes-like object")),
+            (true, false) => Err(PyTypeError::new_err("cannot use a bytes pattern on a string-like object")),
+            _ => Ok(subject),
+        }
+    }
+
+    fn is_bytes(&self) -> bool {
+        matches!(self, Subject::Buffer(..))
+    }
+
+    fn text(&self) -> PyResult<Text<'_>> {
+        match self {
+            Subject::Str(s) => Ok(Text::Str(s.to_str()?)),
+            Subject::Buffer(_, buffer) => {
+                let len = buffer.len_bytes();
+                if len == 0 {
+                    return Ok(Text::Bytes(&[]));
+                }
+                // The buffer stays exported, and therefore valid, while `self` lives.
+                Ok(Text::Bytes(unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, len) }))
+            },
+        }
+    }
+
+    /// Runs `f`, the engine work on this subject, with the GIL released when the
+    /// subject is long enough (see `set_gil_release_threshold`) and cannot be
+    /// modified meanwhile, so other Python threads keep running during the scan.
+    fn detach<T: Ungil, F: Ungil + FnOnce() -> T>(&self, f: F) -> T {
+        let (py, len) = match self {
+            Subject::Str(s) => (s.py(), s.len().unwrap_or(0)),
+            Subject::Buffer(obj, buffer) => (obj.py(), buffer.len_bytes()),
+        };
+        if self.is_immutable() && len >= GIL_RELEASE_THRESHOLD.load(Ordering::Relaxed) {
+            py.detach(f)
+        } else {
+            f()
+        }
+    }
+
+    /// Whether Python code cannot change the contents, which makes it safe to
+    /// read them with the GIL released.
+    fn is_immutable(&self) -> bool {
+        match self {
+            Subject::Str(_) => true,
+            Subject::Buffer(_, buffer) => buffer.readonly(),
+        }
+    }
+
+    fn object(&self) -> Py<PyAny> {
+        match self {
+            Subject::Str(s) => s.clone().into_any().unbind(),
+            Subject::Buffer(obj, _) => obj.clone().unbind(),
+        }
+    }
+}
+
+/// The slice of the subject a search may look at, resolved from Python's
+/// `pos`/`endpos` arguments.
+struct Window {
+    /// Byte offset where the search starts.
+    start: usize,
+    /// Byte offset the subject is truncated at.
+    end: usize,
+    /// `pos` as reported by `Match.pos`.
+    pos: usize,
+    /// `endpos` as reported by `Match.endpos`; `None` means the end of the text.
+    endpos: Option<usize>,
+}
+
+impl Window {
+    /// Negative `pos` and `endpos` count as 0, as in `re`.
+    fn new(text: Text, pos: Option<isize>, endpos: Option<isize>) -> Self {
+        let (pos, endpos) = (pos.map(|p| p.max(0) as usize), endpos.map(|e| e.max(0) as usize));
+        let len = text.as_bytes().len();
+        let (start, pos) = match pos {
+            Some(p) => {
+                let byte = text.to_byte(p);
+                (byte, if byte == len { text.count(0, len) } else { p })
+            },
+            None => (0, 0),
+        };
+        let (end, endpos) = match endpos.map(|e| (text.to_byte(e), e)) {
+            Some((byte, e)) if byte < len => (byte, Some(e)),
+            _ => (len, None),
+        };
+        Window { start, end, pos, endpos }
+    }
+
+    /// Python's `re` finds nothing when `pos` lies beyond `endpos`, except
+    /// for `match` (see `anchored_haystack`).
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.start > self.end
+    }
+
+    #[inline]
+    fn haystack<'t>(&self, text: Text<'t>) -> &'t [u8] {
+        &text.as_bytes()[..self.end]
+    }
+
+    /// The subject of `match`, which `re` still runs when `pos` lies beyond
+    /// `endpos`: the end moves up to `pos`, leaving nothing to consume. `re`'s
+    /// repeats and `$` still see the end as it was, so there `a?` and `$` fail.
+    #[inline]
+    fn anchored_haystack<'t>(&self, text: Text<'t>) -> &'t [u8] {
+        &text.as_bytes()[..self.end.max(self.start)]
+    }
+
+    fn to_match(&self, subject: &Subject, spans: SpanVec, pattern: &Pattern) -> Match {
+        Match::new(subject.object(), spans, pattern, CharAnchor::default(), self.pos, self.endpos)
+    }
+}
 
 #[pyclass(frozen, freelist = 100)]
 pub struct Match {
-    text: Py<PyString>, 
+    text: Py<PyAny>,
     spans: SpanVec,
     group_map: Arc<DashMap<String, usize>>,
+    anchor: CharAnchor,
+    pattern: Pattern,
+    pos: usize,
+    endpos: Option<usize>,
 }
 
 pub struct RuMatch {
     text: String, 
     spans: SpanVec,
     group_map: Arc<DashMap<String, usize>>,
+    nesting: Arc<Vec<usize>>,
 }
 
-impl From<RuMatch> for Match {
-    fn from(rm: RuMatch) -> Self {
+impl RuMatch {
+    /// Wraps the match into a Python `Match` produced by `pattern`.
+    pub fn into_match(self, pattern: &Pattern) -> Match {
         Python::attach(|py| {
             Match {
-                text: PyString::new(py, &rm.text).into(),
-                spans: rm.spans,
-                group_map: rm.group_map.clone(),
+                text: PyString::new(py, &self.text).into_any().unbind(),
+                spans: self.spans,
+                group_map: self.group_map,
+                anchor: CharAnchor::default(),
+                pattern: pattern.clone(),
+                pos: 0,
+                endpos: None,
             }
         })
     }
@@ -44,77 +329,260 @@ impl From<RuMatch> for Match {
 
 #[derive(FromPyObject)]
 enum GroupId {
-    Index(usize),
+    Index(isize),
     Name(String),
 }
 
-#[pymethods]
-impl Match {
-    fn start(&self) -> usize {
-        self.spans.first().map(|(s, _)| *s).unwrap_or(0)
+/// Index of the capturing group that closed last, following Python's `lastindex`.
+///
+/// The group ending furthest to the right wins. Of two groups ending at the
+/// same offset, the later one closed last unless it is nested in the other,
+/// as `nesting` (see `Source::nesting`) tells; without it, a later group
+/// starting no earlier is taken to be nested.
+fn last_index(spans: &SpanVec, nesting: &[usize]) -> Option<usize> {
+    let mut best: Option<(usize, (usize, usize))> = None;
+    for (i, span) in spans.iter().enumerate().skip(1) {
+        if let Some((s, e)) = *span {
+            match best {
+                Some((b, (bs, be))) if e < be || (e == be && nesting.get(b).map_or(s >= bs, |&last| i <= last)) => {},
+                _ => best = Some((i, (s, e))),
+            }
+        }
     }
+    best.map(|(i, _)| i)
+}
 
-    fn end(&self) -> usize {
-        self.spans.first().map(|(_, e)| *e).unwrap_or(0)
+impl Match {
+    fn new(text: Py<PyAny>, spans: SpanVec, pattern: &Pattern, anchor: CharAnchor, pos: usize, endpos: Option<usize>) -> Self {
+        Match {
+            text,
+            spans,
+            group_map: pattern.engine.group_map.clone(),
+            anchor,
+            pattern: pattern.clone(),
+            pos,
+            endpos,
+        }
     }
 
-    #[pyo3(signature = (ident=GroupId::Index(0)))]
-    fn group(&self, py: Python, ident: GroupId) -> PyResult<String> {
+    fn index_of(&self, ident: &GroupId) -> PyResult<usize> {
         let idx = match ident {
-            GroupId::Index(i) => i,
-            GroupId::Name(name) => *self.group_map.get(&name).ok_or_else(|| {
-                PyValueError::new_err(format!("Group name '{}' not defined", name))
+            GroupId::Index(i) => usize::try_from(*i).map_err(|_| PyIndexError::new_err("no such group"))?,
+            GroupId::Name(name) => *self.group_map.get(name).ok_or_else(|| {
+                PyIndexError::new_err("no such group")
             })?
         };
-
-        if let Some((start, end)) = self.spans.get(idx) {
-            let text = self.text.bind(py).to_str()?;
-            Ok(unsafe { text.get_unchecked(*start..*end) }.to_string())
+        if idx < self.spans.len() {
+            Ok(idx)
         } else {
-            Err(PyValueError::new_err(format!("Group {} not found", idx)))
+            Err(PyIndexError::new_err("no such group"))
         }
     }
 
-    fn groups(&self, py: Python) -> PyResult<Vec<Option<String>>> {
-        let text_bind = self.text.bind(py);
-        let text = text_bind.to_str()?;
-        
-        Ok(self.spans.iter().skip(1).map(|(s, e)| {
-            Some(unsafe { text.get_unchecked(*s..*e) }.to_string())
-        }).collect())
+    fn subject<'py>(&self, py: Python<'py>) -> PyResult<Subject<'py>> {
+        Subject::new(self.text.bind(py))
+    }
+
+    fn group_value(&self, py: Python, idx: usize, default: &Py<PyAny>) -> PyResult<Py<PyAny>> {
+        match self.spans[idx] {
+            Some((s, e)) => Ok(self.subject(py)?.text()?.slice(py, s, e).unbind()),
+            None => Ok(default.clone_ref(py)),
+        }
+    }
+
+    /// Python span of group `idx`, or `(-1, -1)` when it did not participate.
+    fn char_span(&self, py: Python, idx: usize) -> PyResult<(isize, isize)> {
+        match self.spans[idx] {
+            Some((s, e)) => {
+                let subject = self.subject(py)?;
+                let text = subject.text()?;
+                let mut anchor = self.anchor;
+                anchor.advance(text, s);
+                Ok((anchor.chars as isize, anchor.to_char(text, e) as isize))
+            },
+            None => Ok((-1, -1)),
+        }
+    }
+}
+
+#[pymethods]
+impl Match {
+    #[pyo3(signature = (ident=GroupId::Index(0)))]
+    fn start(&self, py: Python, ident: GroupId) -> PyResult<isize> {
+        Ok(self.char_span(py, self.index_of(&ident)?)?.0)
     }
 
-    fn lastindex(&self) -> usize {
-        self.spans.len().saturating_sub(1)
+    #[pyo3(signature = (ident=GroupId::Index(0)))]
+    fn end(&self, py: Python, ident: GroupId) -> PyResult<isize> {
+        Ok(self.char_span(py, self.index_of(&ident)?)?.1)
+    }
+
+    #[pyo3(signature = (ident=GroupId::Index(0)))]
+    fn span(&self, py: Python, ident: GroupId) -> PyResult<(isize, isize)> {
+        self.char_span(py, self.index_of(&ident)?)
+    }
+
+    #[pyo3(signature = (*idents))]
+    fn group(&self, py: Python, idents: &Bound<'_, PyTuple>) -> PyResult<Py<PyAny>> {
+        let none = py.None();
+        match idents.len() {
+            0 => self.group_value(py, 0, &none),
+            1 => {
+                let ident: GroupId = idents.get_item(0)?.extract()?;
+                self.group_value(py, self.index_of(&ident)?, &none)
+            },
+            _ => {
+                let values = idents.iter().map(|item| {
+                    let ident: GroupId = item.extract()?;
+                    self.group_value(py, self.index_of(&ident)?, &none)
+                }).collect::<PyResult<Vec<_>>>()?;
+                Ok(PyTuple::new(py, values)?.into_any().unbind())
+            },
+        }
+    }
+
+    fn __getitem__(&self, py: Python, ident: GroupId) -> PyResult<Py<PyAny>> {
+        self.group_value(py, self.index_of(&ident)?, &py.None())
+    }
+
+    #[pyo3(signature = (default=None))]
+    fn groups<'py>(&self, py: Python<'py>, default: Option<Py<PyAny>>) -> PyResult<Bound<'py, PyTuple>> {
+        let default = default.unwrap_or_else(|| py.None());
+        let values = (1..self.spans.len()).map(|i| self.group_value(py, i, &default)).collect::<PyResult<Vec<_>>>()?;
+        PyTuple::new(py, values)
+    }
+
+    #[pyo3(signature = (default=None))]
+    fn groupdict<'py>(&self, py: Python<'py>, default: Option<Py<PyAny>>) -> PyResult<Bound<'py, PyDict>> {
+        let default = default.unwrap_or_else(|| py.None());
+        let mut names: Vec<(usize, String)> = self.group_map.iter().map(|entry| (*entry.value(), entry.key().clone())).collect();
+        names.sort_unstable();
+        let dict = PyDict::new(py);
+        for (idx, name) in names {
+            dict.set_item(name, self.group_value(py, idx, &default)?)?;
+        }
+        Ok(dict)
+    }
+
+    #[getter]
+    fn lastindex(&self) -> Option<usize> {
+        last_index(&self.spans, &self.pattern.engine.nesting)
+    }
+
+    #[getter]
+    fn lastgroup(&self) -> Option<String> {
+        let idx = last_index(&self.spans, &self.pattern.engine.nesting)?;
+        self.group_map.iter().find(|entry| *entry.value() == idx).map(|entry| entry.key().clone())
+    }
+
+    #[getter]
+    fn string(&self, py: Python) -> Py<PyAny> {
+        self.text.clone_ref(py)
+    }
+
+    #[getter]
+    fn re(&self) -> Pattern {
+        self.pattern.clone()
+    }
+
+    #[getter]
+    fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[getter]
+    fn endpos(&self, py: Python) -> PyResult<usize> {
+        match self.endpos {
+            Some(e) => Ok(e),
+            None => {
+                let subject = self.subject(py)?;
+                let text = subject.text()?;
+                Ok(text.count(0, text.as_bytes().len()))
+            },
+        }
     }
 }
 
 impl RuMatch {
     pub fn start(&self) -> usize {
-        self.spans.first().map(|(s, _)| *s).unwrap_or(0)
+        self.spans.first().copied().flatten().map(|(s, _)| s).unwrap_or(0)
     }
 
     pub fn end(&self) -> usize {
-        self.spans.first().map(|(_, e)| *e).unwrap_or(0)
+        self.spans.first().copied().flatten().map(|(_, e)| e).unwrap_or(0)
     }
 
-    pub fn group(&self, _i: i32) -> Result<String, AppError> {
+    pub fn group(&self, _i: i32) -> Result<Option<String>, AppError> {
         let idx = _i as usize;
-        if let Some((start, end)) = self.spans.get(idx) {
-            Ok(unsafe { self.text.get_unchecked(*start..*end) }.to_string())
-        } else {
-             Err(AppError::IndexOutOfBounds(ReError { message: format!("Group {} not found", _i) }))
+        match self.spans.get(idx) {
+            Some(span) => Ok(span.map(|(start, end)| self.slice(start, end))),
+            None => Err(AppError::IndexOutOfBounds(ReError::new(format!("Group {} not found", _i)))),
         }
     }
 
     pub fn groups(&self, _i: i32) -> Result<Vec<Option<String>>, AppError> {
-        Ok(self.spans.iter().skip(1).map(|(s, e)| {
-            Some(unsafe { self.text.get_unchecked(*s..*e) }.to_string())
+        Ok(self.spans.iter().skip(1).map(|span| {
+            span.map(|(s, e)| self.slice(s, e))
         }).collect())
     }
 
-    pub fn lastindex(&self) -> usize {
-        self.spans.len().saturating_sub(1)
+    /// Spans of a bytes engine need not fall on character boundaries.
+    fn slice(&self, start: usize, end: usize) -> String {
+        String::from_utf8_lossy(&self.text.as_bytes()[start..end]).into_owned()
+    }
+
+    pub fn lastindex(&self) -> Option<usize> {
+        last_index(&self.spans, &self.nesting)
+    }
+}
+
+/// Lazily walks the matches of a pattern, producing one `Match` per `__next__`.
+#[pyclass]
+pub struct MatchIterator {
+    text: Py<PyAny>,
+    pattern: Pattern,
+    pos: usize,
+    endpos: usize,
+    match_pos: usize,
+    match_endpos: Option<usize>,
+    last_empty: Option<usize>,
+    anchor: CharAnchor,
+    done: bool,
+}
+
+#[pymethods]
+impl MatchIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<Option<Match>> {
+        if self.done {
+            return Ok(None);
+        }
+        let subject = Subject::new(self.text.bind(py))?;
+        let text = subject.text()?;
+        let haystack = text.as_bytes();
+        // A `bytearray` may have shrunk since the previous match.
+        let haystack = &haystack[..self.endpos.min(haystack.len())];
+        if self.pos > haystack.len() {
+            self.done = true;
+            return Ok(None);
+        }
+        let (engine, pos, last_empty) = (&self.pattern.engine, self.pos, self.last_empty);
+        match subject.detach(|| engine.next_match(haystack, pos, last_empty, true))? {
+            Some(spans) => {
+                let (s, e) = spans[0].unwrap_or_default();
+                self.pos = e;
+                self.last_empty = if s == e { Some(e) } else { None };
+                self.anchor.advance(text, s);
+                Ok(Some(Match::new(self.text.clone_ref(py), spans, &self.pattern, self.anchor, self.match_pos, self.match_endpos)))
+            },
+            None => {
+                self.done = true;
+                Ok(None)
+            }
+        }
     }
 }
 
@@ -130,15 +598,30 @@ pub struct ReConfig {
     size_limit: Option<usize>,
     dfa_size_limit: usize,
     backtrack_limit: Option<usize>,
+    on_match_error: MatchErrorPolicy,
+    syntax: Syntax,
+    /// `.` also matches a newline.
+    dotall: bool,
+    /// Repeats are lazy unless followed by `?`.
+    swap_greed: bool,
+    /// `\r`, `\n` and `\r\n` all end a line for `^`, `$` and `.`.
+    crlf: bool,
+    /// `\w`, `\d`, `\s` and `\b` only match ASCII, like Python's `re.ASCII`.
+    ascii: bool,
+    /// JIT-compile PCRE2 patterns where the platform supports it.
+    jit: bool,
 }
 
 #[pymethods]
 impl ReConfig {
     #[new]
-    #[pyo3(signature = (case_insensitive=false, ignore_whitespace=false, multiline=false, unicode_mode=false, size_limit=None, dfa_size_limit=10_000_000, backtrack_limit=None))]
+    #[pyo3(signature = (case_insensitive=false, ignore_whitespace=false, multiline=false, unicode_mode=false, size_limit=None, dfa_size_limit=10_000_000, backtrack_limit=None, on_match_error=MatchErrorPolicy::Raise, syntax=Syntax::Python, dotall=false, swap_greed=false, crlf=false, ascii=false, jit=true))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         case_insensitive: bool, ignore_whitespace: bool, multiline: bool, unicode_mode: bool,
-        size_limit: Option<usize>, dfa_size_limit: usize, backtrack_limit: Option<usize>
+        size_limit: Option<usize>, dfa_size_limit: usize, backtrack_limit: Option<usize>,
+        on_match_error: MatchErrorPolicy, syntax: Syntax,
+        dotall: bool, swap_greed: bool, crlf: bool, ascii: bool, jit: bool,
     ) -> Self {
         ReConfig {
             case_insensitive,
@@ -148,164 +631,407 @@ impl ReConfig {
             size_limit,
             dfa_size_limit,
             backtrack_limit,
+            on_match_error,
+            syntax,
+            dotall,
+            swap_greed,
+            crlf,
+            ascii,
+            jit,
         }
     }
 }
 
+impl Default for ReConfig {
+    /// The config `ReConfig()` builds.
+    fn default() -> Self {
+        ReConfig::new(
+            false, false, false, false, None, 10_000_000, None, MatchErrorPolicy::Raise, Syntax::Python,
+            false, false, false, false, true,
+        )
+    }
+}
+
+// `re`'s flag values.
+const FLAG_IGNORECASE: u32 = 2;
+const FLAG_LOCALE: u32 = 4;
+const FLAG_MULTILINE: u32 = 8;
+const FLAG_DOTALL: u32 = 16;
+const FLAG_UNICODE: u32 = 32;
+const FLAG_VERBOSE: u32 = 64;
+const FLAG_ASCII: u32 = 256;
+
+impl ReConfig {
+    /// The config for `re`-style integer flags; other bits are ignored, as
+    /// `re` does.
+    fn from_flags(flags: u32, bytes: bool) -> PyResult<Option<ReConfig>> {
+        if flags & FLAG_LOCALE != 0 {
+            return Err(PyValueError::new_err(match bytes {
+                true => "the LOCALE flag is not supported",
+                false => "cannot use LOCALE flag with a str pattern",
+            }));
+        }
+        if flags & FLAG_UNICODE != 0 && bytes {
+            return Err(PyValueError::new_err("cannot use UNICODE flag with a bytes pattern"));
+        }
+        if flags & FLAG_ASCII != 0 && flags & FLAG_UNICODE != 0 {
+            return Err(PyValueError::new_err("ASCII and UNICODE flags are incompatible"));
+        }
+        let set = |flag: u32| flags & flag != 0;
+        if !(set(FLAG_IGNORECASE) || set(FLAG_MULTILINE) || set(FLAG_DOTALL) || set(FLAG_UNICODE) || set(FLAG_VERBOSE) || set(FLAG_ASCII)) {
+            return Ok(None);
+        }
+        Ok(Some(ReConfig {
+            case_insensitive: set(FLAG_IGNORECASE),
+            multiline: set(FLAG_MULTILINE),
+            dotall: set(FLAG_DOTALL),
+            unicode_mode: set(FLAG_UNICODE),
+            ignore_whitespace: set(FLAG_VERBOSE),
+            ascii: set(FLAG_ASCII),
+            ..ReConfig::default()
+        }))
+    }
+
+    /// Whether the engine builders apply the flags that have no inline
+    /// spelling in every engine. Python syntax is translated with them
+    /// resolved, so they only reach the builders for native patterns.
+    fn native(&self) -> bool {
+        self.syntax == Syntax::Native
+    }
+}
+
+/// A `ReConfig`, or `re`-style integer flags such as `reru.I | reru.M`.
+#[derive(FromPyObject)]
+pub enum ConfigArg {
+    Config(ReConfig),
+    Flags(u32),
+}
+
+impl ConfigArg {
+    fn resolve(arg: Option<ConfigArg>, bytes: bool) -> PyResult<Option<ReConfig>> {
+        match arg {
+            None => Ok(None),
+            Some(ConfigArg::Config(config)) => Ok(Some(config)),
+            Some(ConfigArg::Flags(flags)) => ReConfig::from_flags(flags, bytes),
+        }
+    }
+}
+
+/// What a search does when the engine gives up on it, e.g. when PCRE2 hits
+/// its match limit or fancy-regex its `backtrack_limit`.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchErrorPolicy {
+    /// Raise `MatchLimitError`.
+    Raise = 0,
+    /// Report no match, as earlier versions did.
+    NoMatch = 1,
+    /// Retry on the other backtracking engine, raising if it fails too.
+    Fallback = 2,
+}
+
 // --- REGEX STORAGE ---
 #[derive(Debug, Clone)]
 pub struct  ReEngine {
     inner: EngineImpl,
     group_map: Arc<DashMap<String, usize>>,
+    /// Built from a `bytes` pattern: matches raw bytes rather than text.
+    bytes: bool,
+    on_match_error: MatchErrorPolicy,
+    /// The engine `MatchErrorPolicy::Fallback` retries a failed search on.
+    fallback: Option<Arc<ReEngine>>,
+    /// The engine `next_match` retries the offset of an empty match on.
+    nonempty: Option<Arc<ReEngine>>,
+    /// How the groups nest, see `Source::nesting`.
+    nesting: Arc<Vec<usize>>,
 }
 
 #[derive(Debug, Clone)]
 pub enum EngineImpl {
-    Std(Regex),
+    /// A `regex` crate engine, run anchored at the start offset or not, and
+    /// whether its last group holds a newline to cut off the match (see
+    /// `Source::final_newline_group`).
+    Std(meta::Regex, Anchored, bool),
     Pcre2(Pcre2Regex),
     Fancy(Regex2),
+    Literals(Literals),
+}
+
+macro_rules! collect_spans {
+    ($captures:expr) => {{
+        let c = $captures;
+        let mut s = SmallVec::with_capacity(c.len());
+        s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end()))));
+        s
+    }};
+}
+
+/// Rough size of a compiled pattern per byte of its source, for the engines
+/// that do not report their own size.
+pub(crate) const BYTES_PER_PATTERN_BYTE: usize = 128;
+
+/// A search the engine gave up on, see `MatchErrorPolicy`.
+fn match_error(error: impl std::fmt::Display) -> ReError {
+    ReError::new(error.to_string())
 }
 
 impl ReEngine {
 
     #[inline]
-    pub fn is_search(&self, text: &str) -> bool {
-        match &self.inner {
-            EngineImpl::Std(re) => re.is_match(text),
-            EngineImpl::Pcre2(re) => re.is_match(text.as_bytes()).unwrap_or(false),
-            EngineImpl::Fancy(re) => re.is_match(text).unwrap_or(false),
-        }
+    pub fn is_search(&self, text: &str) -> Result<bool, AppError> {
+        self.is_search_at(text.as_bytes(), 0)
     }
 
     #[inline]
-    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
-        match &self.inner {
-            EngineImpl::Std(re) => re.find(text).map(|m| (m.start(), m.end())),
-            EngineImpl::Pcre2(re) => re.find(text.as_bytes()).unwrap_or(None).map(|m| (m.start(), m.end())),
-            EngineImpl::Fancy(re) => re.find(text).unwrap_or(None).map(|m| (m.start(), m.end())),
+    pub fn find(&self, text: &str) -> Result<Option<(usize, usize)>, AppError> {
+        Ok(self.find_at(text.as_bytes(), 0)?.and_then(|s| s[0]))
+    }
+
+    #[inline]
+    pub fn fmatch(&self, text: &str) -> Result<Option<RuMatch>, AppError> {
+        Ok(self.captures_at(text.as_bytes(), 0)?
+            .filter(|s| s[0].is_some_and(|(start, _)| start == 0))
+            .map(|spans| RuMatch { text: text.to_string(), spans, group_map: self.group_map.clone(), nesting: self.nesting.clone() }))
+    }
+
+    /// Splits `text` by the matches like Python's `re.split`: the text of every
+    /// capture group is inserted between the pieces (`None` when it did not
+    /// participate) and at most `maxsplit` splits happen unless it is 0.
+    #[inline]
+    pub fn split(&self, text: &str, maxsplit: usize) -> Result<Vec<Option<String>>, AppError> {
+        Ok(self.split_spans(text.as_bytes(), maxsplit)?.into_iter().map(|span| {
+            span.map(|(s, e)| String::from_utf8_lossy(&text.as_bytes()[s..e]).into_owned())
+        }).collect())
+    }
+
+    /// The byte spans `split` cuts `text` into.
+    pub(crate) fn split_spans(&self, text: &[u8], maxsplit: usize) -> Result<Vec<Option<(usize, usize)>>, AppError> {
+        let captures = self.captures_len() > 1;
+        let mut parts = Vec::new();
+        let mut splits = 0;
+        let mut last = 0;
+        let mut pos = 0;
+        let mut last_empty = None;
+        while maxsplit == 0 || splits < maxsplit {
+            let Some(spans) = self.next_match(text, pos, last_empty, captures)? else { break };
+            let (s, e) = spans[0].unwrap_or_default();
+            parts.push(Some((last, s)));
+            parts.extend(spans.iter().skip(1).copied());
+            splits += 1;
+            last = e;
+            pos = e;
+            last_empty = if s == e { Some(e) } else { None };
         }
+        parts.push(Some((last, text.len())));
+        Ok(parts)
     }
 
     #[inline]
-    pub fn fmatch(&self, text: &str) -> Option<RuMatch> {
-        match &self.inner {
-            EngineImpl::Std(re) => re.captures(text).and_then(|captures| {
-                let mat = captures.get(0).unwrap();
-                if mat.start() == 0 {
-                    let s = captures.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))).collect();
-                    Some(RuMatch { text: text.to_string(), spans: s, group_map: self.group_map.clone() })
-                } else {
-                    None
-                }
-            }),
-            EngineImpl::Pcre2(re) => re.captures(text.as_bytes()).unwrap_or(None).and_then(|captures| {
-                 let mat = captures.get(0).unwrap();
-                 if mat.start() == 0 {
-                    let mut s = SpanVec::new();
-                    for i in 0..captures.len() {
-                        s.push(captures.get(i).map(|m| (m.start(), m.end())).unwrap_or((0,0)));
-                    }
-                    Some(RuMatch { text: text.to_string(), spans: s, group_map: self.group_map.clone() })
-                 } else {
-                     None
-                 }
-            }),
-            EngineImpl::Fancy(re) => re.captures(text).unwrap_or(None).and_then(|captures| {
-                let mat = captures.get(0).unwrap();
-                if mat.start() == 0 {
-                    let s = captures.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))).collect();
-                    Some(RuMatch { text: text.to_string(), spans: s, group_map: self.group_map.clone() })
-                } else {
-                    None
-                }
-            }),
+    pub fn search(&self, text: &str) -> Result<Option<RuMatch>, AppError> {
+        Ok(self.captures_at(text.as_bytes(), 0)?
+            .map(|spans| RuMatch { text: text.to_string(), spans, group_map: self.group_map.clone(), nesting: self.nesting.clone() }))
+    }
+
+    /// Applies `on_match_error` to the outcome of a search; `retry` runs the
+    /// same search on the fallback engine.
+    fn recover<T: Default>(&self, found: Result<T, ReError>, retry: impl FnOnce(&ReEngine) -> Result<T, AppError>) -> Result<T, AppError> {
+        match (found, self.on_match_error, &self.fallback) {
+            (Ok(found), _, _) => Ok(found),
+            (Err(_), MatchErrorPolicy::NoMatch, _) => Ok(T::default()),
+            (Err(_), MatchErrorPolicy::Fallback, Some(fallback)) => retry(fallback),
+            (Err(error), _, _) => Err(AppError::MatchLimit(error)),
         }
     }
 
+    /// Runs the engine from byte offset `start`, letting look-behind and `^` see
+    /// the text that precedes it.
     #[inline]
-    pub fn split(&self, text: &str) -> Vec<String> {
-        match &self.inner {
-            EngineImpl::Std(re) => re.split(text).map(|s| s.to_string()).collect(),
+    pub(crate) fn captures_at(&self, text: &[u8], start: usize) -> Result<Option<SpanVec>, AppError> {
+        let found = match &self.inner {
+            EngineImpl::Std(re, anchored, trim) => {
+                let mut caps = re.create_captures();
+                re.search_captures(&Input::new(text).range(start..).anchored(*anchored), &mut caps);
+                Ok(caps.is_match().then(|| {
+                    let len = caps.group_len() - usize::from(*trim);
+                    let mut s: SpanVec = SmallVec::with_capacity(len);
+                    s.extend((0..len).map(|i| caps.get_group(i).map(|g| (g.start, g.end))));
+                    if let (true, Some((start, _)), Some(newline)) = (*trim, s[0], caps.get_group(len)) {
+                        s[0] = Some((start, newline.start));
+                    }
+                    s
+                }))
+            },
             EngineImpl::Pcre2(re) => {
-                // PCRE2 doesn't have a direct split iterator in the crate, implementing via find_iter
-                let mut last = 0;
-                let mut parts = Vec::new();
-                for m in re.find_iter(text.as_bytes()) {
-                     let m = match m { Ok(m) => m, Err(_) => break };
-                     // Unsafe: PCRE2 UTF mode ensures these are valid char boundaries
-                     parts.push(unsafe { text.get_unchecked(last..m.start()) }.to_string());
-                     last = m.end();
-                }
-                parts.push(unsafe { text.get_unchecked(last..) }.to_string());
-                parts
+                let mut locs = re.capture_locations();
+                re.captures_read_at(&mut locs, text, start).map_err(match_error).map(|found| found.map(|_| {
+                    let mut s = SmallVec::with_capacity(locs.len());
+                    for i in 0..locs.len() {
+                        s.push(locs.get(i));
+                    }
+                    s
+                }))
             },
-            EngineImpl::Fancy(re) => {
-                re.split(text).filter_map(|res| res.ok().map(|x| x.to_string()))
-                .collect()
-            }
-        }
+            EngineImpl::Fancy(re) => re.captures_from_pos(as_str(text), start).map_err(match_error).map(|c| c.map(|c| collect_spans!(c))),
+            EngineImpl::Literals(lits) => Ok(lits.find_at(text, start).map(|s| smallvec![Some(s)])),
+        };
+        self.recover(found, |fallback| fallback.captures_at(text, start))
     }
 
     #[inline]
-    pub fn search(&self, text: &str) -> Result<Option<RuMatch>, AppError> {
-        let spans: Option<SpanVec> = match &self.inner {
-            EngineImpl::Std(re) => re.captures(text).map(|c| {
-                let mut s = SmallVec::with_capacity(c.len());
-                s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))));
-                s
-            }),
-            EngineImpl::Pcre2(re) => re.captures(text.as_bytes()).unwrap_or(None).map(|c| {
-                let mut s = SmallVec::with_capacity(c.len());
-                for i in 0..c.len() {
-                    s.push(c.get(i).map(|m| (m.start(), m.end())).unwrap_or((0,0)));
-                }
-                s
-            }),
-            EngineImpl::Fancy(re) => re.captures(text).unwrap_or(None).map(|c| {
-                let mut s = SmallVec::with_capacity(c.len());
-                s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))));
-                s
-            }),
+    pub(crate) fn is_search_at(&self, text: &[u8], start: usize) -> Result<bool, AppError> {
+        let found = match &self.inner {
+            EngineImpl::Std(re, anchored, _) => Ok(re.is_match(Input::new(text).range(start..).anchored(*anchored))),
+            EngineImpl::Pcre2(re) => re.is_match_at(text, start).map_err(match_error),
+            EngineImpl::Fancy(re) => re.find_from_pos(as_str(text), start).map_err(match_error).map(|m| m.is_some()),
+            EngineImpl::Literals(lits) => Ok(lits.find_at(text, start).is_some()),
         };
-
-        match spans {
-            Some(s) => Ok(Some(RuMatch { text: text.to_string(), spans: s, group_map: self.group_map.clone() })),
-            None => Ok(None)
-        }
+        self.recover(found, |fallback| fallback.is_search_at(text, start))
     }
 
+    /// Like `captures_at`, but only reports the span of the whole match.
     #[inline]
-    pub fn sub(&self, repl: &str, text: &str) -> Result<String, AppError> {
-        match &self.inner {
-            EngineImpl::Std(re) => Ok(re.replace_all(text, repl).into_owned()),
-            EngineImpl::Pcre2(_re) => {
-                let text_bytes = text.as_bytes();
-                let repl_bytes = repl.as_bytes();
-
-                let mut new_bytes = Vec::with_capacity(text_bytes.len());
-                
-                let mut last_index = 0;
-
-                for mat in _re.find_iter(text_bytes) {
-                    if let Ok(m) = mat {
-                        new_bytes.extend_from_slice(&text_bytes[last_index..m.start()]);
-                        new_bytes.extend_from_slice(repl_bytes);
-                        last_index = m.end();
+    pub(crate) fn find_at(&self, text: &[u8], start: usize) -> Result<Option<SpanVec>, AppError> {
+        let span = match &self.inner {
+            EngineImpl::Std(_, _, true) => return Ok(self.captures_at(text, start)?.map(|s| smallvec![s[0]])),
+            EngineImpl::Std(re, anchored, false) => Ok(re.search(&Input::new(text).range(start..).anchored(*anchored)).map(|m| (m.start(), m.end()))),
+            EngineImpl::Pcre2(re) => re.find_at(text, start).map_err(match_error).map(|m| m.map(|m| (m.start(), m.end()))),
+            EngineImpl::Fancy(re) => re.find_from_pos(as_str(text), start).map_err(match_error).map(|m| m.map(|m| (m.start(), m.end()))),
+            EngineImpl::Literals(lits) => Ok(lits.find_at(text, start)),
+        };
+        let found = span.map(|span| span.map(|s| smallvec![Some(s);1]));
+        self.recover(found, |fallback| fallback.find_at(text, start))
+    }
+
+    /// Finds the next match at or after `start` with Python's rules for empty
+    /// matches: one may not sit where the previous empty match (`last_empty`)
+    /// did, but a non-empty match may start there.
+    pub(crate) fn next_match(&self, text: &[u8], mut start: usize, last_empty: Option<usize>, captures: bool) -> Result<Option<SpanVec>, AppError> {
+        loop {
+            if start > text.len() {
+                return Ok(None);
+            }
+            let found = if captures { self.captures_at(text, start)? } else { self.find_at(text, start)? };
+            let Some(spans) = found else { return Ok(None) };
+            let (s, e) = spans[0].unwrap_or_default();
+            if s == e && last_empty == Some(s) {
+                if let Some(nonempty) = &self.nonempty {
+                    let found = if captures { nonempty.captures_at(text, s)? } else { nonempty.find_at(text, s)? };
+                    if found.is_some() {
+                        return Ok(found);
                     }
                 }
+                let Some(&b) = text.get(s) else { return Ok(None) };
+                // Step over one character, or one byte for a bytes engine.
+                start = s + match b {
+                    b if self.bytes || b < 0x80 => 1,
+                    b if b >= 0xf0 => 4,
+                    b if b >= 0xe0 => 3,
+                    _ => 2,
+                };
+                continue;
+            }
+            return Ok(Some(spans));
+        }
+    }
 
-                new_bytes.extend_from_slice(&text_bytes[last_index..]);
-                Ok(unsafe { String::from_utf8_unchecked(new_bytes) })
-            },
-            EngineImpl::Fancy(re) => Ok(re.replace_all(text, repl).into_owned()),
+    /// Spans of every match in `text` from `start`, as `findall` reports them.
+    pub(crate) fn find_spans(&self, text: &[u8], mut start: usize) -> Result<Vec<(usize, usize)>, AppError> {
+        let mut spans = Vec::new();
+        let mut last_empty = None;
+        while let Some(found) = self.next_match(text, start, last_empty, false)? {
+            let (s, e) = found[0].unwrap_or_default();
+            spans.push((s, e));
+            start = e;
+            last_empty = if s == e { Some(e) } else { None };
+        }
+        Ok(spans)
+    }
+
+    /// Approximate bytes held by the compiled engine. PCRE2 and fancy-regex
+    /// do not report their size, so theirs is estimated from the pattern.
+    pub(crate) fn memory_usage(&self) -> usize {
+        match &self.inner {
+            EngineImpl::Std(re, ..) => re.memory_usage(),
+            EngineImpl::Pcre2(re) => re.as_str().len() * BYTES_PER_PATTERN_BYTE,
+            EngineImpl::Fancy(re) => re.as_str().len() * BYTES_PER_PATTERN_BYTE,
+            EngineImpl::Literals(lits) => lits.memory_usage(),
+        }
+    }
+
+    /// This engine, searching only at the start offset. Only the `regex` crate
+    /// engine can; the others anchor with `\G` in the pattern.
+    fn anchored(&self) -> ReEngine {
+        match &self.inner {
+            EngineImpl::Std(re, _, trim) => ReEngine { inner: EngineImpl::Std(re.clone(), Anchored::Yes, *trim), ..self.clone() },
+            _ => self.clone(),
+        }
+    }
+
+    pub fn captures_len(&self) -> usize {
+        match &self.inner {
+            EngineImpl::Std(re, _, trim) => re.captures_len() - usize::from(*trim),
+            EngineImpl::Pcre2(re) => re.captures_len(),
+            EngineImpl::Fancy(re) => re.captures_len(),
+            EngineImpl::Literals(_) => 1,
         }
     }
 
+    /// Replaces the first `count` matches (all of them when `count` is 0) with the
+    /// expanded `template`, returning the new text and the number of replacements.
+    pub fn sub_template(&self, template: &Template, text: &[u8], count: usize) -> Result<(Vec<u8>, usize), AppError> {
+        let literal = template.literal();
+        let mut out = Vec::with_capacity(text.len());
+        let mut replaced = 0;
+        let mut last = 0;
+        let mut pos = 0;
+        let mut last_empty = None;
+        while count == 0 || replaced < count {
+            let Some(spans) = self.next_match(text, pos, last_empty, literal.is_none())? else { break };
+            let (s, e) = spans[0].unwrap_or_default();
+            out.extend_from_slice(&text[last..s]);
+            match literal {
+                Some(lit) => out.extend_from_slice(lit),
+                None => template.expand(text, &spans, &mut out),
+            }
+            replaced += 1;
+            last = e;
+            pos = e;
+            last_empty = if s == e { Some(e) } else { None };
+        }
+        out.extend_from_slice(&text[last..]);
+        Ok((out, replaced))
+    }
+
+    /// Substitution with a `regex`-crate style replacement (`$1`, `${name}`), expanded
+    /// the same way whichever engine backs the pattern.
+    #[inline]
+    pub fn sub(&self, repl: &str, text: &str, count: usize) -> Result<(String, usize), AppError> {
+        let template = Template::parse_rust(repl, &self.group_map, false);
+        let (out, replaced) = self.sub_template(&template, text.as_bytes(), count)?;
+        let out = String::from_utf8(out).unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+        Ok((out, replaced))
+    }
+
+    pub fn kind(&self) -> SelectEngine {
+        match &self.inner {
+            // The literal fast path stands in for the `regex` crate, see `create_engine`.
+            EngineImpl::Std(..) | EngineImpl::Literals(_) => SelectEngine::Std,
+            EngineImpl::Pcre2(_) => SelectEngine::Pcre2,
+            EngineImpl::Fancy(_) => SelectEngine::Fancy,
+        }
+    }
+
+    /// Whether the engine was built from a `bytes` pattern.
+    pub fn is_bytes(&self) -> bool {
+        self.bytes
+    }
+
     pub fn engine_info(&self) -> String {
         match &self.inner {
-            EngineImpl::Std(_) => "regex".to_string(),
+            EngineImpl::Std(..) => "regex".to_string(),
             EngineImpl::Pcre2(_) => "pcre2".to_string(),
             EngineImpl::Fancy(_) => "fancy_regex".to_string(),
+            EngineImpl::Literals(_) => "aho_corasick".to_string(),
         }
     }
 
@@ -318,77 +1044,177 @@ impl ReEngine {
 struct CachedPattern {
     pub engine: Arc<ReEngine>,
     pub match_engine: Arc<ReEngine>,
+    pub fullmatch_engine: Arc<ReEngine>,
+    pub choice: Arc<Choice>,
+    /// Approximate bytes held by the engines, for `cache_info()`.
+    pub memory: usize,
 }
 
-type CacheMap = DashMap<String, Arc<CachedPattern>>;
-type ConfigCacheMap = DashMap<(String, ReConfig), Arc<CachedPattern>>;
+impl CachedPattern {
+    fn pattern(&self) -> Pattern {
+        Pattern {
+            engine: self.engine.clone(),
+            match_engine: self.match_engine.clone(),
+            fullmatch_engine: self.fullmatch_engine.clone(),
+            choice: self.choice.clone(),
+        }
+    }
+}
 
-static CACHE: Lazy<CacheMap> = Lazy::new(|| DashMap::with_capacity(100));
-static CONFIG_CACHE: Lazy<ConfigCacheMap> = Lazy::new(|| DashMap::with_capacity(10));
+/// Compiled patterns by source, kind (`bytes` or not), config (which holds
+/// the syntax mode) and the engine forced by `compile_custom`, if any.
+type CacheKey = (String, bool, Option<ReConfig>, Option<SelectEngine>);
+
+static CACHE: Lazy<LruCache<CacheKey, Arc<CachedPattern>>> = Lazy::new(LruCache::new);
 
 #[pyclass]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SelectEngine{
     Std = 0,
     Fancy = 1,
     Pcre2 = 2,
 }
 
-fn std_engine(pattern: &str, config: Option<&ReConfig>) -> Result<ReEngine, AppError> {
-    let mut builder = RegexBuilder::new(pattern);
+impl SelectEngine {
+    fn dialect(self) -> Dialect {
+        match self {
+            SelectEngine::Std => Dialect::Regex,
+            SelectEngine::Pcre2 => Dialect::Pcre2,
+            SelectEngine::Fancy => Dialect::Fancy,
+        }
+    }
+
+    fn builder(self) -> EngineBuilder {
+        match self {
+            SelectEngine::Std => std_engine,
+            SelectEngine::Pcre2 => pcre2_engine,
+            SelectEngine::Fancy => fancy_engine,
+        }
+    }
+}
+
+/// A `regex-syntax` parser set up like the `regex` builders in `std_engine`,
+/// used to locate the errors they report.
+fn syntax_parser(config: Option<&ReConfig>, bytes: bool) -> regex_syntax::Parser {
+    let mut builder = regex_syntax::ParserBuilder::new();
+    builder.utf8(!bytes).unicode(!bytes);
     if let Some(cfg) = config {
         builder.multi_line(cfg.multiline)
             .case_insensitive(cfg.case_insensitive)
             .ignore_whitespace(cfg.ignore_whitespace)
-            .unicode(cfg.unicode_mode)
-            .dfa_size_limit(cfg.dfa_size_limit);
-        if let Some(sl) = cfg.size_limit { builder.size_limit(sl); }
-    }
-    if let Ok(re) = builder.build(){
-        let names = re.capture_names().map(|n| n.map(|s| s.to_string()));
-        let map = DashMap::new();
-        for (i, name_opt) in names.into_iter().enumerate() {
-            if let Some(name) = name_opt {
-                map.insert(name, i);
+            .dot_matches_new_line(cfg.native() && cfg.dotall)
+            .swap_greed(cfg.native() && cfg.swap_greed)
+            .crlf(cfg.native() && cfg.crlf)
+            .unicode(cfg.unicode_mode && !cfg.ascii && !bytes);
+    }
+    builder.build()
+}
+
+/// Builds a `regex` crate engine. It is driven through `regex-automata`'s meta
+/// regex, which `regex::Regex` wraps, so `match` can run anchored searches at
+/// any offset. It is set up exactly like `regex::RegexBuilder`; a bytes pattern
+/// keeps Unicode mode off, so classes are ASCII-only and `\xHH` matches one byte.
+fn std_engine(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Result<ReEngine, AppError> {
+    let mut syntax = SyntaxConfig::new().utf8(!bytes).unicode(!bytes);
+    // `regex::RegexBuilder`'s defaults.
+    let mut meta = meta::Config::new()
+        .match_kind(MatchKind::LeftmostFirst)
+        .utf8_empty(!bytes)
+        .nfa_size_limit(Some(10 * (1 << 20)))
+        .hybrid_cache_capacity(2 * (1 << 20));
+    if let Some(cfg) = config {
+        syntax = syntax.multi_line(cfg.multiline)
+            .case_insensitive(cfg.case_insensitive)
+            .ignore_whitespace(cfg.ignore_whitespace)
+            .dot_matches_new_line(cfg.native() && cfg.dotall)
+            .swap_greed(cfg.native() && cfg.swap_greed)
+            .crlf(cfg.native() && cfg.crlf)
+            .unicode(cfg.unicode_mode && !cfg.ascii && !bytes);
+        meta = meta.hybrid_cache_capacity(cfg.dfa_size_limit);
+        if let Some(sl) = cfg.size_limit { meta = meta.nfa_size_limit(Some(sl)); }
+    }
+    match meta::Regex::builder().configure(meta).syntax(syntax).build(pattern) {
+        Ok(re) => {
+            let map = DashMap::new();
+            for (i, name_opt) in re.group_info().pattern_names(PatternID::ZERO).enumerate() {
+                if let Some(name) = name_opt {
+                    map.insert(name.to_string(), i);
+                }
             }
-        }
-        return Ok(ReEngine{inner: EngineImpl::Std(re), group_map: Arc::new(map)});
-    };
-    return Err(AppError::RegexError(ReError { message: "Failed to build regex with 'regex' engine.".to_string()}));
+            Ok(ReEngine{inner: EngineImpl::Std(re, Anchored::No, false), group_map: Arc::new(map), bytes, on_match_error: MatchErrorPolicy::Raise, fallback: None, nonempty: None, nesting: Arc::default()})
+        },
+        Err(e) => Err(AppError::RegexError(ReError::from_meta(pattern, e, &mut syntax_parser(config, bytes)))),
+    }
 }
 
-fn pcre2_engine(pattern: &str, config: Option<&ReConfig>) -> Result<ReEngine, AppError> {
+fn pcre2_engine(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Result<ReEngine, AppError> {
     let mut builder = Pcre2RegexBuilder::new();
-    builder.utf(true); // Always enable UTF-8 for compatibility
+    // UTF mode for text patterns; bytes patterns match byte by byte.
+    builder.utf(!bytes);
+    // Without a config, the limits and JIT stack are those of `ReConfig()`, so
+    // both spellings behave the same.
+    let defaults = ReConfig::default();
+    let limits = config.unwrap_or(&defaults);
+    builder.jit_if_available(limits.jit).max_jit_stack_size(Some(limits.dfa_size_limit));
+    // The builder has no match, depth or heap limit options, so the limits go
+    // in as start-of-pattern settings. These can only lower PCRE2's defaults.
+    let mut prefix = String::new();
+    if let Some(limit) = limits.backtrack_limit {
+        prefix.push_str(&format!("(*LIMIT_MATCH={limit})(*LIMIT_DEPTH={limit})"));
+    }
+    if let Some(limit) = limits.size_limit {
+        prefix.push_str(&format!("(*LIMIT_HEAP={})", limit.div_ceil(1024)));
+    }
     if let Some(cfg) = config {
         builder.multi_line(cfg.multiline)
             .caseless(cfg.case_insensitive)
             .extended(cfg.ignore_whitespace)
-            .ucp(cfg.unicode_mode).jit(true);
+            .dotall(cfg.native() && cfg.dotall)
+            .crlf(cfg.native() && cfg.crlf)
+            .ucp(cfg.unicode_mode && !cfg.ascii && !bytes);
+        // The builder has no ungreedy option; the inline flag does the same.
+        if cfg.native() && cfg.swap_greed {
+            prefix.push_str("(?U)");
+        }
     }
-    match builder.build(pattern) {
+    match builder.build(&format!("{}{}", prefix, pattern)) {
         Ok(re) => {
 
-            let names = re.capture_names().iter().map(|n| n.clone());
+            let names = re.capture_names().iter().cloned();
             let map = DashMap::new();
             for (i, name_opt) in names.into_iter().enumerate() {
                 if let Some(name) = name_opt {
                     map.insert(name, i);
                 }
             }
-            Ok(ReEngine{inner: EngineImpl::Pcre2(re), group_map: Arc::new(map)})
+            Ok(ReEngine{inner: EngineImpl::Pcre2(re), group_map: Arc::new(map), bytes, on_match_error: MatchErrorPolicy::Raise, fallback: None, nonempty: None, nesting: Arc::default()})
         },
-        Err(e) => Err(AppError::RegexError(ReError { message: format!("PCRE2 error: {}", e)})),
+        Err(e) => Err(AppError::RegexError(ReError::from_pcre2(pattern, &prefix, e))),
     }
 }
 
-fn fancy_engine(pattern: &str, config: Option<&ReConfig>) -> Result<ReEngine, AppError> {
-    let mut builder = RegexBuilder2::new(pattern);
+fn fancy_engine(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Result<ReEngine, AppError> {
+    if bytes {
+        return Err(AppError::RegexError(ReError::at("fancy-regex does not support bytes patterns", pattern, None)));
+    }
+    if config.is_some_and(|cfg| cfg.native() && cfg.crlf) {
+        return Err(AppError::RegexError(ReError::at("fancy-regex does not support CRLF mode", pattern, None)));
+    }
+    // fancy-regex forwards the builder's multi_line flag to the `regex` engine it
+    // delegates to, which turns `\A` into a line anchor; an inline flag keeps it
+    // an end-of-text anchor. It has no swap_greed option but takes the flag.
+    let mut prefix = String::new();
     if let Some(cfg) = config {
-        builder.multi_line(cfg.multiline)
-                .case_insensitive(cfg.case_insensitive)
+        if cfg.multiline { prefix.push_str("(?m)"); }
+        if cfg.native() && cfg.swap_greed { prefix.push_str("(?U)"); }
+    }
+    let mut builder = RegexBuilder2::new(&format!("{}{}", prefix, pattern));
+    if let Some(cfg) = config {
+        builder.case_insensitive(cfg.case_insensitive)
                 .ignore_whitespace(cfg.ignore_whitespace)
-                .unicode_mode(cfg.unicode_mode)
+                .dot_matches_new_line(cfg.native() && cfg.dotall)
+                // fancy-regex ignores the `(?u)` opening a translated pattern.
+                .unicode_mode(!cfg.native() || cfg.unicode_mode && !cfg.ascii)
                 .delegate_dfa_size_limit(cfg.dfa_size_limit);
         if let Some(bl) = cfg.backtrack_limit { builder.backtrack_limit(bl); }
         if let Some(sl) = cfg.size_limit { builder.delegate_size_limit(sl); }
@@ -402,33 +1228,170 @@ fn fancy_engine(pattern: &str, config: Option<&ReConfig>) -> Result<ReEngine, Ap
                     map.insert(name, i);
                 }
             }
-            Ok(ReEngine{inner: EngineImpl::Fancy(re), group_map: Arc::new(map)})
+            Ok(ReEngine{inner: EngineImpl::Fancy(re), group_map: Arc::new(map), bytes: false, on_match_error: MatchErrorPolicy::Raise, fallback: None, nonempty: None, nesting: Arc::default()})
         },
-        Err(e) => Err(AppError::RegexError(ReError { message: format!("Regex error: {}", e)})),
+        Err(e) => Err(AppError::RegexError(ReError::from_fancy(pattern, &prefix, e))),
+    }
+}
+
+type EngineBuilder = fn(&str, Option<&ReConfig>, bool) -> Result<ReEngine, AppError>;
+
+/// The engines `create_engine` picks from, best first, named as in `engine_info`.
+/// fancy-regex only searches text, so bytes patterns stop at PCRE2.
+fn tiers(bytes: bool) -> &'static [(&'static str, SelectEngine)] {
+    const TIERS: [(&str, SelectEngine); 3] = [
+        ("regex", SelectEngine::Std),
+        ("pcre2", SelectEngine::Pcre2),
+        ("fancy_regex", SelectEngine::Fancy),
+    ];
+    match bytes {
+        true => &TIERS[..2],
+        false => &TIERS,
+    }
+}
+
+/// Builds `kind`'s engine for `source`, spelled in its dialect.
+fn build_engine(kind: SelectEngine, source: &Source, anchor: Anchor, config: Option<&ReConfig>, bytes: bool) -> Result<ReEngine, AppError> {
+    let pattern = source.dialect(kind.dialect(), anchor)?;
+    let mut engine = kind.builder()(&pattern, config, bytes)?;
+    if let EngineImpl::Std(_, _, trim) = &mut engine.inner {
+        *trim = source.final_newline_group(anchor);
+    }
+    Ok(engine)
+}
+
+/// Builds `source` on the selected engine, or on the one its features call
+/// for (see `analysis::choose`), and says why. Alternations of plain literals
+/// get an Aho-Corasick automaton instead. When every engine fails, the error
+/// carries each one's reason.
+fn create_engine(source: &Source, anchor: Anchor, config: Option<&ReConfig>, engine: Option<SelectEngine>, bytes: bool) -> Result<(ReEngine, String), AppError> {
+    let (mut engine, reason) = pick_engine(source, anchor, config, engine, bytes)?;
+    if engine.captures_len() > 1 {
+        engine.nesting = Arc::new(source.nesting(config, bytes));
     }
+    engine.on_match_error = config.map_or(MatchErrorPolicy::Raise, |cfg| cfg.on_match_error);
+    if engine.on_match_error == MatchErrorPolicy::Fallback {
+        // Only the backtracking engines give up on a search.
+        let other = match engine.inner {
+            EngineImpl::Pcre2(_) => SelectEngine::Fancy,
+            EngineImpl::Fancy(_) => SelectEngine::Pcre2,
+            _ => return Ok((engine, reason)),
+        };
+        engine.fallback = build_engine(other, source, anchor, config, bytes).ok().map(Arc::new);
+    }
+    Ok((engine, reason))
+}
+
+/// The engine `create_engine` builds, before its `MatchErrorPolicy` is applied.
+///
+/// The candidates come best first; a later one is only built when an earlier
+/// one fails, e.g. on hitting `size_limit`.
+fn pick_engine(source: &Source, anchor: Anchor, config: Option<&ReConfig>, engine: Option<SelectEngine>, bytes: bool) -> Result<(ReEngine, String), AppError> {
+    if let Some(kind) = engine {
+        return Ok((build_engine(kind, source, anchor, config, bytes)?, "selected with compile_custom".to_string()));
+    }
+    if anchor == Anchor::Search && let Some(lits) = Literals::new(source.raw(), config, bytes) {
+        let engine = ReEngine{inner: EngineImpl::Literals(lits), group_map: Arc::new(DashMap::new()), bytes, on_match_error: MatchErrorPolicy::Raise, fallback: None, nonempty: None, nesting: Arc::default()};
+        return Ok((engine, "alternation of plain literals".to_string()));
+    }
+    let (candidates, reason) = analysis::choose(source.features(), bytes);
+    let mut engine_errors: Vec<(&str, ReError)> = Vec::new();
+    for (name, kind) in &candidates {
+        match build_engine(*kind, source, anchor, config, bytes) {
+            Ok(engine) if engine_errors.is_empty() => return Ok((engine, reason)),
+            Ok(engine) => {
+                let failed: Vec<String> = engine_errors.iter().map(|(name, e)| format!("{} failed: {}", name, e.report())).collect();
+                return Ok((engine, failed.join("; ")));
+            },
+            Err(AppError::RegexError(e)) => engine_errors.push((*name, e)),
+            Err(e) => return Err(e),
+        }
+    }
+    let engine_errors = tiers(bytes).iter().map(|(name, _)| {
+        let tried = engine_errors.iter().position(|(tried, _)| tried == name);
+        (*name, tried.map_or_else(|| ReError::new(format!("not tried: {}", reason)), |i| engine_errors[i].1.clone()))
+    }).collect();
+    Err(AppError::RegexError(ReError::all_failed(source.raw(), engine_errors)))
 }
 
-fn create_engine(pattern: &str, config: Option<&ReConfig>, engine: Option<SelectEngine>) -> Result<ReEngine, AppError> {
-    match engine {
-        None => {
-            if let Ok(re_engine) = std_engine(pattern, config) {
-                Ok(re_engine)
-            } else if let Ok(pcre_engine) = pcre2_engine(pattern, config) {
-                Ok(pcre_engine)
-            } else {
-                fancy_engine(pattern, config)
+/// Spells a `bytes` pattern in engine syntax: non-ASCII bytes become `\xHH`
+/// escapes, which the byte-oriented engines read as that raw byte.
+fn bytes_pattern(pattern: &[u8]) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut escaped = false;
+    for &b in pattern {
+        if b.is_ascii() {
+            out.push(b as char);
+            escaped = !escaped && b == b'\\';
+        } else {
+            // A backslash before a non-ASCII byte just makes it literal.
+            if escaped {
+                out.pop();
             }
-        },
-        Some(SelectEngine::Std) => {
-            std_engine(pattern, config)
-        },
-        Some(SelectEngine::Pcre2) => {
-             pcre2_engine(pattern, config)
-        },
-        Some(SelectEngine::Fancy) => {
-            fancy_engine(pattern, config)
-        },
+            out.push_str(&format!("\\x{:02X}", b));
+            escaped = false;
+        }
+    }
+    out
+}
+
+/// Reads a `str` or `bytes` pattern as engine syntax, and whether it was bytes.
+fn pattern_source<'a>(pattern: &'a Bound<'_, PyAny>) -> PyResult<(Cow<'a, str>, bool)> {
+    if let Ok(s) = pattern.cast::<PyString>() {
+        return Ok((Cow::Borrowed(s.to_str()?), false));
+    }
+    if let Ok(b) = pattern.cast::<PyBytes>() {
+        return Ok((Cow::Owned(bytes_pattern(b.as_bytes())), true));
+    }
+    Err(PyTypeError::new_err("first argument must be string or compiled pattern"))
+}
+
+// --- BATCH EXECUTION ---
+
+/// Thread pools for the `*_many` methods, keyed by their number of threads.
+static THREAD_POOLS: Lazy<DashMap<usize, Arc<ThreadPool>>> = Lazy::new(DashMap::new);
+
+/// The pool running at most `n_threads` threads, or `None` for rayon's global
+/// pool, which has one per core. Asking for that many or more gets the global
+/// pool, so at most one pool per core count below it is ever started.
+fn thread_pool(n_threads: usize) -> PyResult<Option<Arc<ThreadPool>>> {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if n_threads == 0 || n_threads >= cores {
+        return Ok(None);
+    }
+    if let Some(pool) = THREAD_POOLS.get(&n_threads) {
+        return Ok(Some(pool.clone()));
     }
+    let pool = ThreadPoolBuilder::new().num_threads(n_threads).build()
+        .map_err(|e| PyRuntimeError::new_err(format!("failed to start thread pool: {}", e)))?;
+    Ok(Some(THREAD_POOLS.entry(n_threads).or_insert_with(|| Arc::new(pool)).clone()))
+}
+
+/// Runs `f` over every subject in parallel and returns the results in input
+/// order. `n_threads` bounds the parallelism; by default every core is used.
+///
+/// The GIL is released for the whole batch unless a subject is a writable
+/// buffer, which Python code could otherwise modify during the scan.
+fn map_many<T, F>(py: Python, subjects: &[Subject], n_threads: Option<usize>, f: F) -> PyResult<Vec<T>>
+where
+    T: Send,
+    F: Fn(Text) -> Result<T, AppError> + Send + Sync,
+{
+    let texts = subjects.iter().map(Subject::text).collect::<PyResult<Vec<_>>>()?;
+    let pool = n_threads.map(thread_pool).transpose()?.flatten();
+    let run = || {
+        let scan = || texts.par_iter().map(|text| f(*text)).collect();
+        match &pool {
+            Some(pool) => pool.install(scan),
+            None => scan(),
+        }
+    };
+    let found: Result<Vec<T>, AppError> = if subjects.iter().all(Subject::is_immutable) {
+        py.detach(run)
+    } else {
+        run()
+    };
+    Ok(found?)
 }
 
 // --- MAIN API ---
@@ -438,12 +1401,136 @@ fn create_engine(pattern: &str, config: Option<&ReConfig>, engine: Option<Select
 pub struct Pattern {
     engine: Arc<ReEngine>,
     match_engine: Arc<ReEngine>,
+    fullmatch_engine: Arc<ReEngine>,
+    choice: Arc<Choice>,
+}
+
+impl Pattern {
+    /// Reads the subject of a search, which must be text for a `str` pattern and
+    /// bytes-like for a `bytes` pattern.
+    fn subject<'py>(&self, text: &Bound<'py, PyAny>) -> PyResult<Subject<'py>> {
+        Subject::for_pattern(text, self.engine.is_bytes())
+    }
+
+    fn anchored_captures(&self, window: &Window, subject: &Subject, text: Text) -> Result<Option<SpanVec>, AppError> {
+        Ok(subject.detach(|| self.match_engine.captures_at(window.anchored_haystack(text), window.start))?
+            .filter(|s| s[0].is_some_and(|(start, _)| start == window.start)))
+    }
+
+    fn anchored_find(&self, window: &Window, subject: &Subject, text: Text) -> Result<Option<SpanVec>, AppError> {
+        Ok(subject.detach(|| self.match_engine.find_at(window.anchored_haystack(text), window.start))?
+            .filter(|s| s[0].is_some_and(|(start, _)| start == window.start)))
+    }
+
+    /// Replaces matches with the text returned by calling `repl` on their `Match`.
+    fn sub_callable(&self, repl: &Bound<'_, PyAny>, subject: &Subject, count: usize) -> PyResult<(Vec<u8>, usize)> {
+        let text = subject.text()?;
+        let haystack = text.as_bytes();
+        let mut out = Vec::with_capacity(haystack.len());
+        let mut anchor = CharAnchor::default();
+        let mut replaced = 0;
+        let mut last = 0;
+        let mut pos = 0;
+        let mut last_empty = None;
+        while count == 0 || replaced < count {
+            let Some(spans) = subject.detach(|| self.engine.next_match(haystack, pos, last_empty, true))? else { break };
+            let (s, e) = spans[0].unwrap_or_default();
+            anchor.advance(text, s);
+            let m = Match::new(subject.object(), spans, self, anchor, 0, None);
+            let replacement = repl.call1((m,))?;
+            out.extend_from_slice(&haystack[last..s]);
+            self.push_replacement(&replacement, &mut out)?;
+            replaced += 1;
+            last = e;
+            pos = e;
+            last_empty = if s == e { Some(e) } else { None };
+        }
+        out.extend_from_slice(&haystack[last..]);
+        Ok((out, replaced))
+    }
+
+    /// Appends a replacement returned by a callable, which must be of the same
+    /// kind as the pattern; `re` takes `None` for an empty one.
+    fn push_replacement(&self, replacement: &Bound<'_, PyAny>, out: &mut Vec<u8>) -> PyResult<()> {
+        if replacement.is_none() {
+            return Ok(());
+        }
+        let found = || replacement.get_type().name().map(|n| n.to_string()).unwrap_or_default();
+        if !self.engine.is_bytes() {
+            let replacement = replacement.cast::<PyString>().map_err(|_| {
+                PyTypeError::new_err(format!("expected str instance, {} found", found()))
+            })?;
+            out.extend_from_slice(replacement.to_str()?.as_bytes());
+            return Ok(());
+        }
+        match Subject::new(replacement) {
+            Ok(subject) if subject.is_bytes() => out.extend_from_slice(subject.text()?.as_bytes()),
+            _ => return Err(PyTypeError::new_err(format!("expected a bytes-like object, {} found", found()))),
+        }
+        Ok(())
+    }
+
+    fn subn_impl(&self, repl: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, count: usize, rust_syntax: bool) -> PyResult<(Py<PyAny>, usize)> {
+        let py = text.py();
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let (out, replaced) = match self.template(repl, rust_syntax)? {
+            Some(template) => subject.detach(|| self.engine.sub_template(&template, text.as_bytes(), count))?,
+            None => self.sub_callable(repl, &subject, count)?,
+        };
+        Ok((text.new_like(py, &out).unbind(), replaced))
+    }
+
+    /// Reads every subject of a batch, see `subject`.
+    fn subjects<'py>(&self, texts: &Bound<'py, PyAny>) -> PyResult<Vec<Subject<'py>>> {
+        texts.try_iter()?.map(|text| self.subject(&text?)).collect()
+    }
+
+    /// Parses `repl` for `sub`, or returns `None` when it is a callable.
+    fn template(&self, repl: &Bound<'_, PyAny>, rust_syntax: bool) -> PyResult<Option<Template>> {
+        let Some(repl) = self.repl_source(repl)? else { return Ok(None) };
+        let latin1 = self.engine.is_bytes();
+        Ok(Some(if rust_syntax {
+            Template::parse_rust(&repl, &self.engine.group_map, latin1)
+        } else {
+            Template::parse_python(&repl, self.engine.captures_len() - 1, &self.engine.group_map, latin1)?
+        }))
+    }
+
+    /// The replacement string `repl` as template syntax, `None` for a callable.
+    /// Templates for bytes patterns are read as Latin-1, like `re` does.
+    fn repl_source<'a>(&self, repl: &'a Bound<'_, PyAny>) -> PyResult<Option<Cow<'a, str>>> {
+        if !self.engine.is_bytes() {
+            if let Ok(repl) = repl.cast::<PyString>() {
+                return Ok(Some(Cow::Borrowed(repl.to_str()?)));
+            }
+        } else if let Some(subject) = Subject::new(repl).ok().filter(Subject::is_bytes) {
+            return Ok(Some(Cow::Owned(subject.text()?.as_bytes().iter().map(|&b| b as char).collect())));
+        }
+        if !repl.is_callable() {
+            let expected = if self.engine.is_bytes() { "a bytes-like object" } else { "a str" };
+            return Err(PyTypeError::new_err(format!(
+                "repl must be {} or callable, not {}", expected, repl.get_type().name()?
+            )));
+        }
+        Ok(None)
+    }
 }
 
 #[pymethods]
 impl Pattern {
-    pub fn engine_info(&self) -> String {
-        self.engine.engine_info()
+    /// Names the engine, followed with `verbose` by why it was picked.
+    #[pyo3(signature = (verbose=false))]
+    pub fn engine_info(&self, verbose: bool) -> String {
+        match verbose {
+            true => format!("{} ({})", self.engine.engine_info(), self.choice.reason),
+            false => self.engine.engine_info(),
+        }
+    }
+
+    /// The constructs that call for a backtracking engine, e.g. `["backrefs"]`.
+    pub fn features(&self) -> Vec<&'static str> {
+        self.choice.features.names()
     }
 
     pub fn group_names(&self) -> Vec<String> {
@@ -451,252 +1538,520 @@ impl Pattern {
         names
     }
 
-    pub fn is_search(&self, text: &Bound<'_, PyString>) -> PyResult<bool> {
-        let text_slice = text.to_str()?;
-        Ok(self.engine.is_search(text_slice))
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn is_search(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<bool> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(false);
+        }
+        Ok(subject.detach(|| self.engine.is_search_at(window.haystack(text), window.start))?)
     }
 
-    pub fn is_match(&self, text: &Bound<'_, PyString>) -> PyResult<bool> {
-        let text_slice = text.to_str()?;
-        Ok(self.match_engine.is_search(text_slice))
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn is_match(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<bool> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        Ok(self.anchored_find(&window, &subject, text)?.is_some())
     }
 
-    pub fn find(&self, text: &Bound<'_, PyString>) -> PyResult<Option<Match>> {
-        let text_slice = text.to_str()?;
-        
-        let spans = match &self.engine.inner {
-            EngineImpl::Std(re) => re.find(text_slice).map(|m| smallvec![(m.start(), m.end());1]),
-            EngineImpl::Pcre2(re) => re.find(text_slice.as_bytes()).unwrap_or(None).map(|m| smallvec![(m.start(), m.end());1]),
-            EngineImpl::Fancy(re) => re.find(text_slice).unwrap_or(None).map(|m| smallvec![(m.start(), m.end());1]),
-        };
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn find(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Option<Match>> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(None);
+        }
+        let spans = subject.detach(|| self.engine.find_at(window.haystack(text), window.start))?;
+        Ok(spans.map(|s| window.to_match(&subject, s, self)))
+    }
 
-        match spans {
-            Some(s) => Ok(Some(Match { text: text.clone().unbind(), spans: s, group_map: self.engine.group_map.clone() })),
-            None => Ok(None)
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn findall<'py>(&self, text: &Bound<'py, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Vec<Bound<'py, PyAny>>> {
+        let py = text.py();
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        let haystack = window.haystack(text);
+        if window.is_empty() {
+            return Ok(Vec::new());
         }
+        let spans = subject.detach(|| self.engine.find_spans(haystack, window.start))?;
+        Ok(spans.into_iter().map(|(s, e)| text.slice(py, s, e)).collect())
     }
 
-    pub fn findall(&self, text: &str) -> PyResult<Vec<String>> {
-        Ok(match &self.engine.inner {
-            EngineImpl::Std(re) => re.find_iter(text).map(|mat| mat.as_str().to_string()).collect(),
-            EngineImpl::Pcre2(re) => re.find_iter(text.as_bytes()).filter_map(|res| res.ok().map(|mat| text[mat.start()..mat.end()].to_string())).collect(),
-            EngineImpl::Fancy(re) => re.find_iter(text).filter_map(|res| res.ok().map(|mat| mat.as_str().to_string())).collect(),
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn finditer(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<MatchIterator> {
+        let subject = self.subject(text)?;
+        let window = Window::new(subject.text()?, pos, endpos);
+        Ok(MatchIterator {
+            text: subject.object(),
+            pattern: self.clone(),
+            pos: window.start,
+            endpos: window.end,
+            match_pos: window.pos,
+            match_endpos: window.endpos,
+            last_empty: None,
+            anchor: CharAnchor::default(),
+            done: window.is_empty(),
         })
     }
 
-    #[pyo3(name = "match")]
-    pub fn fmatch(&self, text: &Bound<'_, PyString>) -> PyResult<Option<Match>> {
-        let text_slice = text.to_str()?;
-        // return self.engine.search(text_slice)?; faster with code replication
-        let spans: Option<SpanVec> = match &self.match_engine.inner {
-            EngineImpl::Std(re) => re.captures(text_slice).map(|c| c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))).collect()),
-            EngineImpl::Pcre2(re) => re.captures(text_slice.as_bytes()).unwrap_or(None).map(|c| {
-                let mut s = SpanVec::new();
-                for i in 0..c.len() {
-                    s.push(c.get(i).map(|m| (m.start(), m.end())).unwrap_or((0,0)));
-                }
-                s
-            }),
-            EngineImpl::Fancy(re) => re.captures(text_slice).unwrap_or(None).map(|c| {
-                let mut s = SpanVec::with_capacity(c.len());
-                s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))));
-                s
-            }),
-        };
+    #[pyo3(name = "match", signature = (text, pos=None, endpos=None))]
+    pub fn fmatch(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Option<Match>> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        Ok(self.anchored_captures(&window, &subject, text)?.map(|s| window.to_match(&subject, s, self)))
+    }
 
-        match spans {
-            Some(s) => Ok(Some(Match { text: text.clone().unbind(), spans: s, group_map: self.engine.group_map.clone() })),
-            None => Ok(None)
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn fullmatch(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Option<Match>> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(None);
         }
+        let spans = subject.detach(|| self.fullmatch_engine.captures_at(window.haystack(text), window.start))?
+            .filter(|s| s[0].is_some_and(|(start, _)| start == window.start));
+        Ok(spans.map(|s| window.to_match(&subject, s, self)))
     }
 
-    fn find_indices(&self, text: &Bound<'_, PyString>) -> PyResult<Option<(usize, usize)>> {
-        let text_slice = text.to_str()?;
-        Ok(self.engine.find(text_slice))
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn is_fullmatch(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<bool> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(false);
+        }
+        Ok(subject.detach(|| self.fullmatch_engine.find_at(window.haystack(text), window.start))?
+            .is_some_and(|s| s[0].is_some_and(|(start, _)| start == window.start)))
     }
 
-    pub fn search(&self, text: &Bound<'_, PyString>) -> PyResult<Option<Match>> {
-        let text_slice = text.to_str()?;
-        // return self.engine.search(text_slice)?; faster with code replication
-        let spans: Option<SpanVec> = match &self.engine.inner {
-            EngineImpl::Std(re) => re.captures(text_slice).map(|c| {
-                let mut s = SmallVec::with_capacity(c.len());
-                s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))));
-                s
-            }),
-            EngineImpl::Pcre2(re) => re.captures(text_slice.as_bytes()).unwrap_or(None).map(|c| {
-                let mut s = SpanVec::new();
-                for i in 0..c.len() {
-                    s.push(c.get(i).map(|m| (m.start(), m.end())).unwrap_or((0,0)));
-                }
-                s
-            }),
-            EngineImpl::Fancy(re) => re.captures(text_slice).unwrap_or(None).map(|c| {
-                let mut s = SpanVec::new();
-                s.extend(c.iter().map(|m| m.map(|x| (x.start(), x.end())).unwrap_or((0,0))));
-                s
-            }),
-        };
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    fn find_indices(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Option<(usize, usize)>> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(None);
+        }
+        let found = subject.detach(|| self.engine.find_at(window.haystack(text), window.start))?;
+        Ok(found.and_then(|s| s[0]).map(|(s, e)| {
+            let mut anchor = CharAnchor::default();
+            anchor.advance(text, s);
+            (anchor.chars, anchor.to_char(text, e))
+        }))
+    }
 
-        match spans {
-            Some(s) => Ok(Some(Match { text: text.clone().unbind(), spans: s, group_map: self.engine.group_map.clone() })),
-            None => Ok(None)
+    #[pyo3(signature = (text, pos=None, endpos=None))]
+    pub fn search(&self, text: &Bound<'_, PyAny>, pos: Option<isize>, endpos: Option<isize>) -> PyResult<Option<Match>> {
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        let window = Window::new(text, pos, endpos);
+        if window.is_empty() {
+            return Ok(None);
         }
+        let spans = subject.detach(|| self.engine.captures_at(window.haystack(text), window.start))?;
+        Ok(spans.map(|s| window.to_match(&subject, s, self)))
+    }
+
+    #[pyo3(signature = (repl, text, count=0, *, rust_syntax=false))]
+    pub fn sub(&self, repl: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, count: usize, rust_syntax: bool) -> PyResult<Py<PyAny>> {
+        Ok(self.subn_impl(repl, text, count, rust_syntax)?.0)
+    }
+
+    #[pyo3(signature = (repl, text, count=0, *, rust_syntax=false))]
+    pub fn subn(&self, repl: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, count: usize, rust_syntax: bool) -> PyResult<(Py<PyAny>, usize)> {
+        self.subn_impl(repl, text, count, rust_syntax)
+    }
+
+    #[pyo3(signature = (text, maxsplit=0))]
+    pub fn split<'py>(&self, text: &Bound<'py, PyAny>, maxsplit: usize) -> PyResult<Vec<Option<Bound<'py, PyAny>>>> {
+        let py = text.py();
+        let subject = self.subject(text)?;
+        let text = subject.text()?;
+        Ok(subject.detach(|| self.engine.split_spans(text.as_bytes(), maxsplit))?.into_iter()
+            .map(|span| span.map(|(s, e)| text.slice(py, s, e)))
+            .collect())
+    }
+
+    /// `is_search` over every subject of `texts`, run in parallel.
+    #[pyo3(signature = (texts, n_threads=None))]
+    pub fn is_search_many(&self, py: Python, texts: &Bound<'_, PyAny>, n_threads: Option<usize>) -> PyResult<Vec<bool>> {
+        let subjects = self.subjects(texts)?;
+        map_many(py, &subjects, n_threads, |text| self.engine.is_search_at(text.as_bytes(), 0))
+    }
+
+    /// `search` over every subject of `texts`, run in parallel.
+    #[pyo3(signature = (texts, n_threads=None))]
+    pub fn search_many(&self, py: Python, texts: &Bound<'_, PyAny>, n_threads: Option<usize>) -> PyResult<Vec<Option<Match>>> {
+        let subjects = self.subjects(texts)?;
+        let found = map_many(py, &subjects, n_threads, |text| self.engine.captures_at(text.as_bytes(), 0))?;
+        Ok(subjects.iter().zip(found).map(|(subject, spans)| {
+            spans.map(|s| Match::new(subject.object(), s, self, CharAnchor::default(), 0, None))
+        }).collect())
     }
 
-    pub fn sub(&self, repl: &str, text: &Bound<'_, PyString>) -> PyResult<String> {
-        let text_slice = text.to_str()?;
-        Ok(self.engine.sub(repl, text_slice)?)
+    /// `findall` over every subject of `texts`, run in parallel.
+    #[pyo3(signature = (texts, n_threads=None))]
+    pub fn findall_many<'py>(&self, texts: &Bound<'py, PyAny>, n_threads: Option<usize>) -> PyResult<Vec<Vec<Bound<'py, PyAny>>>> {
+        let py = texts.py();
+        let subjects = self.subjects(texts)?;
+        let found = map_many(py, &subjects, n_threads, |text| self.engine.find_spans(text.as_bytes(), 0))?;
+        subjects.iter().zip(found).map(|(subject, spans)| {
+            let text = subject.text()?;
+            Ok(spans.into_iter().map(|(s, e)| text.slice(py, s, e)).collect())
+        }).collect()
+    }
+
+    /// `sub` over every subject of `texts`, run in parallel. A callable `repl`
+    /// needs the GIL for every match, so the subjects are then handled in turn.
+    #[pyo3(signature = (repl, texts, count=0, n_threads=None, *, rust_syntax=false))]
+    pub fn sub_many(&self, repl: &Bound<'_, PyAny>, texts: &Bound<'_, PyAny>, count: usize, n_threads: Option<usize>, rust_syntax: bool) -> PyResult<Vec<Py<PyAny>>> {
+        let py = texts.py();
+        let subjects = self.subjects(texts)?;
+        let Some(template) = self.template(repl, rust_syntax)? else {
+            return subjects.iter().map(|subject| {
+                let (out, _) = self.sub_callable(repl, subject, count)?;
+                Ok(subject.text()?.new_like(py, &out).unbind())
+            }).collect();
+        };
+        let replaced = map_many(py, &subjects, n_threads, |text| {
+            self.engine.sub_template(&template, text.as_bytes(), count).map(|(out, _)| out)
+        })?;
+        subjects.iter().zip(replaced).map(|(subject, out)| {
+            Ok(subject.text()?.new_like(py, &out).unbind())
+        }).collect()
     }
 
     #[staticmethod]
-    pub fn escape(text: &Bound<'_, PyString>) -> PyResult<String> {
-        let text_slice = text.to_str()?;
-        Ok(ReEngine::escape(text_slice)?)
+    pub fn escape<'py>(text: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let py = text.py();
+        if let Ok(text) = text.cast::<PyString>() {
+            return Ok(PyString::new(py, &ReEngine::escape(text.to_str()?)?).into_any());
+        }
+        // Escape the Latin-1 decoding: only ASCII characters are special.
+        let subject = Subject::new(text)?;
+        let decoded: String = subject.text()?.as_bytes().iter().map(|&b| b as char).collect();
+        let escaped: Vec<u8> = ReEngine::escape(&decoded)?.chars().map(|c| c as u8).collect();
+        Ok(PyBytes::new(py, &escaped).into_any())
     }
 }
 
-fn has_match(pattern: &str) -> bool {
+fn has_match(pattern: &str, config: Option<&ReConfig>) -> bool {
     let mut char_iter = pattern.chars();
     match char_iter.next() {
-        Some('^') => true,
-        Some('\\') => {
-            match char_iter.next() {
-                Some('A') => true,
-                _ => false,
-            }
-        },
+        // Under multiline mode `^` also matches after every newline.
+        Some('^') => !config.is_some_and(|cfg| cfg.multiline),
+        Some('\\') => matches!(char_iter.next(), Some('A')),
         _ => false,
-    } 
+    }
 }
 
+/// Builds the search engine plus the start-anchored and fully-anchored variants
+/// used by `match` and `fullmatch`, all on the same engine as the main pattern.
+/// The engine `next_match` tries where an empty match of `engine` was just
+/// found, matching only non-empty text there as `re` does since Python 3.7.
+/// Only patterns that match both empty and non-empty text need one. The
+/// `regex` crate cannot express it, so a Python pattern it runs borrows a
+/// backtracking engine; a native one keeps the crate's own semantics.
+/// fancy-regex goes first for text, as PCRE2 validates the whole subject as
+/// UTF-8 on every search.
+fn nonempty_engine(source: &Source, engine: &ReEngine, config: Option<&ReConfig>, bytes: bool) -> Option<Arc<ReEngine>> {
+    if source.width().is_some_and(|(lo, hi)| lo > 0 || hi == Some(0)) {
+        return None;
+    }
+    let kinds: &[SelectEngine] = match (&engine.inner, source) {
+        (EngineImpl::Literals(_), _) | (EngineImpl::Std(..), Source::Native(..)) => &[],
+        (EngineImpl::Std(..), Source::Python(_)) if bytes => &[SelectEngine::Pcre2],
+        (EngineImpl::Std(..), Source::Python(_)) => &[SelectEngine::Fancy, SelectEngine::Pcre2],
+        (EngineImpl::Pcre2(_), _) => &[SelectEngine::Pcre2],
+        (EngineImpl::Fancy(_), _) => &[SelectEngine::Fancy],
+    };
+    kinds.iter().find_map(|&kind| create_engine(source, Anchor::NonEmpty, config, Some(kind), bytes).ok()).map(|(e, _)| Arc::new(e))
+}
 
-#[pyfunction]
-#[pyo3(signature = (pattern, config=None))]
-pub fn compile(pattern: &str, config: Option<ReConfig>) -> Result<Pattern, AppError> {
-    if config.is_none() {
-        if let Some(entry) = CACHE.get(pattern) {
-            let cached = entry.value();
-            return Ok(Pattern {
-                engine: cached.engine.clone(),
-                match_engine: cached.match_engine.clone(),
-            });
-        }
-    } else  {
-        let cfg = config.unwrap();
-        let key = (pattern.to_string(), cfg); 
-        if let Some(entry) = CONFIG_CACHE.get(&key) {
-            let cached = entry.value();
-            return Ok(Pattern {
-                engine: cached.engine.clone(),
-                match_engine: cached.match_engine.clone(),
-            });
-        }
-    }
-    let has_anchored_start = has_match(pattern);
-    
-    let engine = Arc::new(create_engine(pattern, config.as_ref(), None)?);
-    let match_engine = if has_anchored_start {
+fn nonempty_memory(engine: &ReEngine) -> usize {
+    engine.nonempty.as_ref().map_or(0, |nonempty| nonempty.memory_usage())
+}
+
+fn build_engines(pattern: &str, config: Option<&ReConfig>, select_engine: Option<SelectEngine>, bytes: bool) -> Result<CachedPattern, AppError> {
+    let source = Source::new(pattern, config, bytes)?;
+    let (mut engine, reason) = create_engine(&source, Anchor::Search, config, select_engine, bytes)?;
+    engine.nonempty = nonempty_engine(&source, &engine, config, bytes);
+    let engine = Arc::new(engine);
+    let choice = Arc::new(Choice { features: source.features().clone(), reason });
+    if let EngineImpl::Literals(lits) = &engine.inner {
+        let anchored = |anchor| Arc::new(ReEngine {
+            inner: EngineImpl::Literals(lits.anchored(anchor)),
+            group_map: engine.group_map.clone(),
+            bytes,
+            on_match_error: engine.on_match_error,
+            fallback: None,
+            nonempty: None,
+            nesting: Arc::default(),
+        });
+        let (match_engine, fullmatch_engine) = (anchored(Anchor::Start), anchored(Anchor::Full));
+        // The anchored variants share the automaton.
+        let memory = engine.memory_usage();
+        return Ok(CachedPattern { engine, match_engine, fullmatch_engine, choice, memory });
+    }
+    // The `regex` crate has no `\G`: its engines run `match` and `fullmatch` as
+    // anchored searches, which pin the match to any start offset without
+    // scanning past it.
+    if let EngineImpl::Std(..) = &engine.inner {
+        let match_engine = Arc::new(engine.anchored());
+        let fullmatch_engine = Arc::new(create_engine(&source, Anchor::Full, config, Some(SelectEngine::Std), bytes)?.0.anchored());
+        let memory = engine.memory_usage() + fullmatch_engine.memory_usage() + nonempty_memory(&engine);
+        return Ok(CachedPattern { engine, match_engine, fullmatch_engine, choice, memory });
+    }
+    let selected = Some(engine.kind());
+    let match_engine = if has_match(pattern, config) {
         engine.clone()
     } else {
-        let modified_pattern = format!("^(?:{})", pattern);
-        Arc::new(create_engine(&modified_pattern, config.as_ref(), None)?)
+        Arc::new(create_engine(&source, Anchor::Start, config, selected, bytes)?.0)
     };
+    let fullmatch_engine = Arc::new(create_engine(&source, Anchor::Full, config, selected, bytes)?.0);
+    let mut memory = engine.memory_usage() + fullmatch_engine.memory_usage() + nonempty_memory(&engine);
+    if !Arc::ptr_eq(&match_engine, &engine) {
+        memory += match_engine.memory_usage();
+    }
+    Ok(CachedPattern { engine, match_engine, fullmatch_engine, choice, memory })
+}
 
-    let cached_entry = Arc::new(CachedPattern {
-        engine: engine.clone(),
-        match_engine: match_engine.clone(),
-    });
-
-    if let Some(cfg) = config {
-        CONFIG_CACHE.insert((pattern.to_string(), cfg), cached_entry);
-    } else {
-        CACHE.insert(pattern.to_string(), cached_entry);
+/// Compiles `pattern` (in engine syntax) through the cache, unless `cache` is false.
+fn compile_source(pattern: &str, bytes: bool, config: Option<ReConfig>, select_engine: Option<SelectEngine>, cache: bool) -> Result<Pattern, AppError> {
+    if !cache {
+        return Ok(build_engines(pattern, config.as_ref(), select_engine, bytes)?.pattern());
     }
+    let key = (pattern.to_string(), bytes, config, select_engine);
+    if let Some(entry) = CACHE.get(&key) {
+        return Ok(entry.pattern());
+    }
+    let cached_entry = Arc::new(build_engines(pattern, config.as_ref(), select_engine, bytes)?);
+    let compiled = cached_entry.pattern();
+    let size = cached_entry.memory + pattern.len();
+    CACHE.insert(key, cached_entry, size);
+    Ok(compiled)
+}
 
-    Ok(Pattern { engine, match_engine })
+#[pyfunction]
+#[pyo3(signature = (pattern, config=None, *, cache=true))]
+pub fn compile(pattern: &Bound<'_, PyAny>, config: Option<ConfigArg>, cache: bool) -> PyResult<Pattern> {
+    let (source, bytes) = pattern_source(pattern)?;
+    let config = ConfigArg::resolve(config, bytes)?;
+    Ok(compile_source(&source, bytes, config, None, cache)?)
 }
 
 #[pyfunction]
-#[pyo3(signature = (pattern, config=None, select_engine=None))]
-pub fn compile_custom(pattern: &str, config: Option<ReConfig>, select_engine: Option<SelectEngine>) -> Result<Pattern, AppError> {
-    let has_match = has_match(pattern);
-    match (config, has_match) {
-        (None, true) => {
-            let engine = Arc::new(create_engine(&pattern, None, select_engine)?);
-            Ok(Pattern { engine: Arc::clone(&engine), match_engine: engine })
-        },
-        (None, false) => {
-            let engine = Arc::new(create_engine(&pattern, None, select_engine)?);
-            let modified_pattern = format!("^(?:{})", pattern);
-            let match_engine = Arc::new(create_engine(&modified_pattern, None, select_engine)?);
-            Ok(Pattern { engine, match_engine })
-        },
-        (Some(cfg), true) => {
-            let engine = Arc::new(create_engine(&pattern, Some(&cfg), select_engine)?);
-            Ok(Pattern { engine: Arc::clone(&engine), match_engine: engine })
+#[pyo3(signature = (pattern, config=None, select_engine=None, *, cache=true))]
+pub fn compile_custom(pattern: &Bound<'_, PyAny>, config: Option<ConfigArg>, select_engine: Option<SelectEngine>, cache: bool) -> PyResult<Pattern> {
+    let (source, bytes) = pattern_source(pattern)?;
+    let config = ConfigArg::resolve(config, bytes)?;
+    Ok(compile_source(&source, bytes, config, select_engine, cache)?)
+}
+
+/// Reports whether each engine accepts `pattern` and why not, building every
+/// one of them; `compile` only builds the one `analysis::choose` picks. A
+/// pattern that is not valid Python syntax leads with a `python_syntax`
+/// verdict, and no engine is tried.
+#[pyfunction]
+#[pyo3(signature = (pattern, config=None))]
+pub fn explain_compile(pattern: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<Vec<(&'static str, bool, String)>> {
+    let (pattern, bytes) = pattern_source(pattern)?;
+    let config = ConfigArg::resolve(config, bytes)?;
+    let source = match Source::new(&pattern, config.as_ref(), bytes) {
+        Ok(source) => source,
+        Err(e) => {
+            let untried = || "not tried: the pattern is not valid Python syntax".to_string();
+            let mut verdicts = vec![("python_syntax", false, e.report()), ("aho_corasick", false, untried())];
+            verdicts.extend(tiers(bytes).iter().map(|(name, _)| (*name, false, untried())));
+            if bytes {
+                verdicts.push(("fancy_regex", false, "fancy-regex does not support bytes patterns".to_string()));
+            }
+            return Ok(verdicts);
         },
-        (Some(cfg), false) => {
-            let engine = Arc::new(create_engine(&pattern, Some(&cfg), select_engine)?);
-            let modified_pattern = format!("^(?:{})", pattern);
-            let match_engine = Arc::new(create_engine(&modified_pattern, Some(&cfg), select_engine)?);
-            Ok(Pattern { engine, match_engine })
-        }
+    };
+    let mut verdicts = vec![match Literals::new(&pattern, config.as_ref(), bytes) {
+        Some(_) => ("aho_corasick", true, "alternation of plain literals".to_string()),
+        None => ("aho_corasick", false, "not an alternation of plain literals".to_string()),
+    }];
+    for (name, kind) in tiers(bytes) {
+        verdicts.push(match build_engine(*kind, &source, Anchor::Search, config.as_ref(), bytes) {
+            Ok(_) => (*name, true, "compiled".to_string()),
+            Err(AppError::RegexError(e)) => (*name, false, e.report()),
+            Err(e) => return Err(e.into()),
+        });
     }
+    if bytes {
+        verdicts.push(("fancy_regex", false, "fancy-regex does not support bytes patterns".to_string()));
+    }
+    Ok(verdicts)
 }
 
 #[pyfunction]
 #[pyo3(signature = (pattern, text, config=None))]
-pub fn is_match(pattern: &str, text: &Bound<'_, PyString>, config: Option<ReConfig>) -> PyResult<bool> {
-    let pattern = compile(pattern, config)?;
-    pattern.is_match(text)
+pub fn is_match(
+pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<bool> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.is_match(text, None, None)
 }
 
 #[pyfunction]
 #[pyo3(signature = (pattern, text, config=None))]
-pub fn is_search(pattern: &str, text: &Bound<'_, PyString>, config: Option<ReConfig>) -> PyResult<bool> {
-    let pattern = compile(pattern, config)?;
-    pattern.is_search(text)
+pub fn is_search(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<bool> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.is_search(text, None, None)
 }
 
 #[pyfunction]
 #[pyo3(name = "match", signature = (pattern, text, config=None))]
-pub fn find(pattern: &str, text: &Bound<'_, PyString>, config: Option<ReConfig>) -> PyResult<Option<Match>> {
-    let pattern = compile(pattern, config)?;
-    pattern.fmatch(text)
+pub fn find(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<Option<Match>> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.fmatch(text, None, None)
+}
+
+#[pyfunction]
+#[pyo3(signature = (pattern, text, config=None))]
+pub fn fullmatch(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<Option<Match>> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.fullmatch(text, None, None)
+}
+
+#[pyfunction]
+#[pyo3(signature = (pattern, text, config=None))]
+pub fn is_fullmatch(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<bool> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.is_fullmatch(text, None, None)
 }
 
 #[pyfunction]
 #[pyo3(signature = (pattern, text, config=None))]
-pub fn search(pattern: &str, text: &Bound<'_, PyString>, config: Option<ReConfig>) -> PyResult<Option<Match>> {
-    let pattern = compile(pattern, config)?;
-    pattern.search(text)
+pub fn search(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<Option<Match>> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.search(text, None, None)
+}
+#[pyfunction]
+#[pyo3(signature = (pattern, text, config=None))]
+pub fn finditer(pattern: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<MatchIterator> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.finditer(text, None, None)
+}
+#[pyfunction]
+#[pyo3(signature = (pattern, repl, text, config=None, count=0, *, rust_syntax=false))]
+pub fn sub(pattern: &Bound<'_, PyAny>, repl: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>, count: usize, rust_syntax: bool) -> PyResult<Py<PyAny>> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.sub(repl, text, count, rust_syntax)
 }
 #[pyfunction]
-#[pyo3(signature = (pattern, repl, text, config=None))]
-pub fn sub(pattern: &str, repl: &str, text: &Bound<'_, PyString>, config: Option<ReConfig>) -> PyResult<String> {
-    let pattern = compile(pattern, config)?;
-    pattern.sub(repl, text)
+#[pyo3(signature = (pattern, repl, text, config=None, count=0, *, rust_syntax=false))]
+pub fn subn(pattern: &Bound<'_, PyAny>, repl: &Bound<'_, PyAny>, text: &Bound<'_, PyAny>, config: Option<ConfigArg>, count: usize, rust_syntax: bool) -> PyResult<(Py<PyAny>, usize)> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.subn(repl, text, count, rust_syntax)
+}
+#[pyfunction]
+#[pyo3(signature = (pattern, text, config=None, maxsplit=0))]
+pub fn split<'py>(pattern: &Bound<'_, PyAny>, text: &Bound<'py, PyAny>, config: Option<ConfigArg>, maxsplit: usize) -> PyResult<Vec<Option<Bound<'py, PyAny>>>> {
+    let pattern = compile(pattern, config, true)?;
+    pattern.split(text, maxsplit)
 }
 #[pyfunction]
 #[pyo3(signature = (text))]
-pub fn escape(text: &Bound<'_, PyString>) -> PyResult<String> {
-    Ok(Pattern::escape(text)?)
+pub fn escape<'py>(text: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    Pattern::escape(text)
+}
+
+/// Sets the subject length (in characters for `str`, bytes otherwise) from which
+/// searches release the GIL; 0 releases it for every search.
+#[pyfunction]
+pub fn set_gil_release_threshold(size: usize) {
+    GIL_RELEASE_THRESHOLD.store(size, Ordering::Relaxed);
+}
+
+#[pyfunction]
+pub fn get_gil_release_threshold() -> usize {
+    GIL_RELEASE_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Sets how many compiled patterns, and separately pattern sets, are cached,
+/// evicting the least recently used ones beyond it; 0 disables caching.
+#[pyfunction]
+pub fn set_cache_size(size: usize) {
+    cache::set_capacity(size);
+    CACHE.shrink();
+    pattern_set::shrink_cache();
+}
+
+#[pyfunction]
+pub fn get_cache_size() -> usize {
+    cache::capacity()
+}
+
+/// Clears the caches of compiled patterns and pattern sets, and resets the
+/// statistics of `cache_info`.
+#[pyfunction]
+pub fn purge() {
+    CACHE.clear();
+    pattern_set::purge_cache();
+}
+
+/// Hits, misses, evictions, size and approximate memory of the compile cache;
+/// the cache of pattern sets is left out.
+#[pyfunction]
+pub fn cache_info() -> CacheInfo {
+    CACHE.info()
 }
 
 #[pymodule]
 fn reru(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Match>()?;
+    m.add_class::<MatchIterator>()?;
     m.add_class::<ReConfig>()?;
     m.add_class::<Pattern>()?;
     m.add_class::<SelectEngine>()?;
+    m.add_class::<MatchErrorPolicy>()?;
+    m.add_class::<Syntax>()?;
+    m.add_class::<PatternSet>()?;
+    m.add_class::<CacheInfo>()?;
+    exceptions::register(m)?;
+    // `re`'s integer flags, for `compile(pattern, reru.I | reru.M)`.
+    for (name, value) in [
+        ("NOFLAG", 0),
+        ("I", FLAG_IGNORECASE), ("IGNORECASE", FLAG_IGNORECASE),
+        ("L", FLAG_LOCALE), ("LOCALE", FLAG_LOCALE),
+        ("M", FLAG_MULTILINE), ("MULTILINE", FLAG_MULTILINE),
+        ("S", FLAG_DOTALL), ("DOTALL", FLAG_DOTALL),
+        ("U", FLAG_UNICODE), ("UNICODE", FLAG_UNICODE),
+        ("X", FLAG_VERBOSE), ("VERBOSE", FLAG_VERBOSE),
+        ("A", FLAG_ASCII), ("ASCII", FLAG_ASCII),
+    ] {
+        m.add(name, value)?;
+    }
     m.add_function(wrap_pyfunction!(compile, m)?)?;
     m.add_function(wrap_pyfunction!(compile_custom, m)?)?;
+    m.add_function(wrap_pyfunction!(explain_compile, m)?)?;
     m.add_function(wrap_pyfunction!(is_match, m)?)?;
     m.add_function(wrap_pyfunction!(is_search, m)?)?;
     m.add_function(wrap_pyfunction!(find, m)?)?;
     m.add_function(wrap_pyfunction!(search, m)?)?;
+    m.add_function(wrap_pyfunction!(fullmatch, m)?)?;
+    m.add_function(wrap_pyfunction!(is_fullmatch, m)?)?;
+    m.add_function(wrap_pyfunction!(finditer, m)?)?;
     m.add_function(wrap_pyfunction!(sub, m)?)?;
+    m.add_function(wrap_pyfunction!(subn, m)?)?;
+    m.add_function(wrap_pyfunction!(split, m)?)?;
     m.add_function(wrap_pyfunction!(escape, m)?)?;
+    m.add_function(wrap_pyfunction!(set_gil_release_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(get_gil_release_threshold, m)?)?;
+    m.add_function(wrap_pyfunction!(set_cache_size, m)?)?;
+    m.add_function(wrap_pyfunction!(get_cache_size, m)?)?;
+    m.add_function(wrap_pyfunction!(purge, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_info, m)?)?;
     Ok(())
 }
\ No newline at end of file
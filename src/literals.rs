@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use aho_corasick::{AhoCorasick, Anchored, Input, MatchKind, StartKind};
+
+use crate::ReConfig;
+use crate::syntax::{Anchor, Syntax};
+
+/// An alternation of plain literals (`foo|bar|baz`) run on an Aho-Corasick
+/// automaton, which builds and scans thousands of keywords far faster than a
+/// general regex engine.
+#[derive(Debug, Clone)]
+pub struct Literals {
+    searcher: AhoCorasick,
+    anchor: Anchor,
+    /// The alternatives, ASCII-lowercased when matching case-insensitively,
+    /// for `Full` lookups.
+    set: Arc<HashSet<Vec<u8>>>,
+    ascii_case_insensitive: bool,
+}
+
+/// Reads one alternative as a literal, or `None` if it uses any regex syntax.
+///
+/// Accepts `\t`, `\n`, `\r`, `\f`, `\v` and `\xHH`, which is a code point for
+/// `str` patterns and a raw byte for `bytes` patterns, and escaped punctuation.
+/// Native syntax gives some escaped punctuation a meaning, like `\<` for a word
+/// start, so there only escaped meta characters are taken.
+fn parse_literal(alternative: &str, syntax: Syntax, bytes: bool) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(alternative.len());
+    let mut chars = alternative.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = match chars.next()? {
+                    't' => '\t',
+                    'n' => '\n',
+                    'r' => '\r',
+                    'f' => '\x0c',
+                    'v' => '\x0b',
+                    'x' => {
+                        let hex: String = [chars.next()?, chars.next()?].iter().collect();
+                        let value = u8::from_str_radix(&hex, 16).ok()?;
+                        if bytes {
+                            out.push(value);
+                            continue;
+                        }
+                        char::from(value)
+                    },
+                    c if syntax == Syntax::Native && regex_syntax::is_meta_character(c) => c,
+                    c if syntax == Syntax::Python && c.is_ascii_punctuation() => c,
+                    _ => return None,
+                };
+                out.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+            },
+            '.' | '^' | '$' | '*' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '|' => return None,
+            c => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    Some(out)
+}
+
+/// Splits `pattern` into its literal alternatives when it is nothing but an
+/// alternation of two or more non-empty literals.
+fn parse_alternation(pattern: &str, syntax: Syntax, bytes: bool) -> Option<Vec<Vec<u8>>> {
+    let mut alternatives = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in pattern.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '|' => {
+                alternatives.push(&pattern[start..i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    alternatives.push(&pattern[start..]);
+    if alternatives.len() < 2 {
+        return None;
+    }
+    alternatives.into_iter()
+        .map(|alternative| parse_literal(alternative, syntax, bytes).filter(|literal| !literal.is_empty()))
+        .collect()
+}
+
+impl Literals {
+    /// Builds the engine if `pattern` is a literal alternation it can run with
+    /// the same results as the regex engines under `config`.
+    pub fn new(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Option<Literals> {
+        if config.is_some_and(|cfg| cfg.ignore_whitespace) {
+            return None;
+        }
+        let syntax = config.map_or(Syntax::Python, |cfg| cfg.syntax);
+        let literals = parse_alternation(pattern, syntax, bytes)?;
+        let case_insensitive = config.is_some_and(|cfg| cfg.case_insensitive);
+        if case_insensitive {
+            // Only ASCII case folding is available, and in Unicode mode `k` and
+            // `s` also fold to the Kelvin sign and the long s. Python syntax
+            // matches text in Unicode mode unless `ascii` is set.
+            let unicode = !bytes && config.is_some_and(|cfg| !cfg.ascii && (cfg.unicode_mode || syntax == Syntax::Python));
+            let foldable = |b: &u8| b.is_ascii() && !(unicode && matches!(b.to_ascii_lowercase(), b'k' | b's'));
+            if !literals.iter().all(|literal| literal.iter().all(foldable)) {
+                return None;
+            }
+        }
+        let searcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostFirst)
+            .start_kind(StartKind::Both)
+            .ascii_case_insensitive(case_insensitive)
+            .build(&literals)
+            .ok()?;
+        let set = literals.into_iter()
+            .map(|literal| if case_insensitive { literal.to_ascii_lowercase() } else { literal })
+            .collect();
+        Some(Literals {
+            searcher,
+            anchor: Anchor::Search,
+            set: Arc::new(set),
+            ascii_case_insensitive: case_insensitive,
+        })
+    }
+
+    /// Approximate bytes held by the automaton and the set of alternatives.
+    pub fn memory_usage(&self) -> usize {
+        self.searcher.memory_usage() + self.set.iter().map(Vec::len).sum::<usize>()
+    }
+
+    /// The same alternation, matching only where `anchor` allows.
+    pub fn anchored(&self, anchor: Anchor) -> Literals {
+        Literals { anchor, ..self.clone() }
+    }
+
+    /// Span of the match at or after `start`, picking the first alternative
+    /// among those starting leftmost, like Python's `re`.
+    pub fn find_at(&self, text: &[u8], start: usize) -> Option<(usize, usize)> {
+        if start > text.len() {
+            return None;
+        }
+        match self.anchor {
+            Anchor::Search => self.searcher.find(Input::new(text).range(start..)),
+            // The alternatives are never empty.
+            Anchor::Start | Anchor::NonEmpty => self.searcher.find(Input::new(text).range(start..).anchored(Anchored::Yes)),
+            Anchor::Full => {
+                let rest = &text[start..];
+                let found = match self.ascii_case_insensitive {
+                    true => self.set.contains(&rest.to_ascii_lowercase()),
+                    false => self.set.contains(rest),
+                };
+                return found.then_some((start, text.len()));
+            },
+        }.map(|m| (m.start(), m.end()))
+    }
+}
@@ -0,0 +1,264 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use regex::{RegexSet, RegexSetBuilder};
+use regex::bytes::{RegexSet as BytesRegexSet, RegexSetBuilder as BytesRegexSetBuilder};
+
+use crate::cache::LruCache;
+use crate::exceptions::{AppError, ReError};
+use crate::{create_engine, BYTES_PER_PATTERN_BYTE, map_many, pattern_source, ConfigArg, ReConfig, ReEngine, Subject, Text};
+use crate::syntax::{Anchor, Dialect, Source};
+
+#[derive(Debug)]
+enum SetImpl {
+    Str(RegexSet),
+    Bytes(BytesRegexSet),
+}
+
+/// The compiled engines of a `PatternSet`.
+#[derive(Debug)]
+struct SetEngines {
+    /// One automaton for every pattern the `regex` crate accepts.
+    set: Option<SetImpl>,
+    /// Index of every member of `set` among the patterns.
+    set_indices: Vec<usize>,
+    /// Patterns that need PCRE2 or fancy-regex, with their index.
+    others: Vec<(usize, ReEngine)>,
+    len: usize,
+    bytes: bool,
+}
+
+type SetCacheKey = (Vec<String>, bool, Option<ReConfig>);
+
+static SET_CACHE: Lazy<LruCache<SetCacheKey, Arc<SetEngines>>> = Lazy::new(LruCache::new);
+
+pub fn shrink_cache() {
+    SET_CACHE.shrink();
+}
+
+pub fn purge_cache() {
+    SET_CACHE.clear();
+}
+
+fn build_set(patterns: &[&str], config: Option<&ReConfig>, bytes: bool) -> Result<SetImpl, regex::Error> {
+    if bytes {
+        let mut builder = BytesRegexSetBuilder::new(patterns);
+        builder.unicode(false);
+        if let Some(cfg) = config {
+            builder.multi_line(cfg.multiline)
+                .case_insensitive(cfg.case_insensitive)
+                .ignore_whitespace(cfg.ignore_whitespace)
+                .dot_matches_new_line(cfg.native() && cfg.dotall)
+                .swap_greed(cfg.native() && cfg.swap_greed)
+                .crlf(cfg.native() && cfg.crlf)
+                .dfa_size_limit(cfg.dfa_size_limit);
+            if let Some(sl) = cfg.size_limit { builder.size_limit(sl); }
+        }
+        return builder.build().map(SetImpl::Bytes);
+    }
+    let mut builder = RegexSetBuilder::new(patterns);
+    if let Some(cfg) = config {
+        builder.multi_line(cfg.multiline)
+            .case_insensitive(cfg.case_insensitive)
+            .ignore_whitespace(cfg.ignore_whitespace)
+            .dot_matches_new_line(cfg.native() && cfg.dotall)
+            .swap_greed(cfg.native() && cfg.swap_greed)
+            .crlf(cfg.native() && cfg.crlf)
+            .unicode(cfg.unicode_mode && !cfg.ascii)
+            .dfa_size_limit(cfg.dfa_size_limit);
+        if let Some(sl) = cfg.size_limit { builder.size_limit(sl); }
+    }
+    builder.build().map(SetImpl::Str)
+}
+
+impl SetEngines {
+    /// Puts every pattern the `regex` crate accepts into one `RegexSet` and
+    /// compiles the others on their own, on the first engine that takes them.
+    fn new(patterns: &[String], config: Option<&ReConfig>, bytes: bool) -> Result<Self, AppError> {
+        let in_pattern = |i: usize, e: ReError| AppError::RegexError(ReError { message: format!("pattern {}: {}", i, e), ..e });
+        let sources = patterns.iter().enumerate()
+            .map(|(i, pattern)| Source::new(pattern, config, bytes).map_err(|e| in_pattern(i, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Patterns the `regex` crate cannot express in its dialect stay out of the set.
+        let spelled: Vec<Option<_>> = sources.iter()
+            .map(|source| source.dialect(Dialect::Regex, Anchor::Search).ok())
+            .collect();
+        let all: Option<Vec<&str>> = spelled.iter().map(|pattern| pattern.as_deref()).collect();
+        if let Some(set) = all.and_then(|all| build_set(&all, config, bytes).ok()) {
+            return Ok(SetEngines {
+                set: Some(set),
+                set_indices: (0..patterns.len()).collect(),
+                others: Vec::new(),
+                len: patterns.len(),
+                bytes,
+            });
+        }
+        let mut set_indices = Vec::new();
+        let mut others = Vec::new();
+        for (i, source) in sources.iter().enumerate() {
+            if spelled[i].as_deref().is_some_and(|pattern| build_set(&[pattern], config, bytes).is_ok()) {
+                set_indices.push(i);
+            } else {
+                let (engine, _) = create_engine(source, Anchor::Search, config, None, bytes).map_err(|e| match e {
+                    AppError::RegexError(e) => in_pattern(i, e),
+                    e => e,
+                })?;
+                others.push((i, engine));
+            }
+        }
+        let members: Vec<&str> = set_indices.iter().map(|&i| spelled[i].as_deref().unwrap()).collect();
+        let set = match members.is_empty() {
+            true => None,
+            false => Some(build_set(&members, config, bytes).map_err(|e| {
+                AppError::RegexError(ReError::new(e.to_string()))
+            })?),
+        };
+        Ok(SetEngines { set, set_indices, others, len: patterns.len(), bytes })
+    }
+
+    /// Approximate bytes held by the engines. `RegexSet` does not report its
+    /// size, so it is estimated from its patterns.
+    fn memory_usage(&self) -> usize {
+        let members = match &self.set {
+            Some(SetImpl::Str(set)) => set.patterns(),
+            Some(SetImpl::Bytes(set)) => set.patterns(),
+            None => &[],
+        };
+        members.iter().map(|p| p.len() * BYTES_PER_PATTERN_BYTE).sum::<usize>()
+            + self.others.iter().map(|(_, engine)| engine.memory_usage()).sum::<usize>()
+    }
+
+    /// Indices of all the patterns matching somewhere in `text`, in ascending order.
+    fn matches(&self, text: &[u8]) -> Result<Vec<usize>, AppError> {
+        let mut found: Vec<usize> = match &self.set {
+            Some(SetImpl::Str(set)) => set.matches(crate::as_str(text)).into_iter().map(|i| self.set_indices[i]).collect(),
+            Some(SetImpl::Bytes(set)) => set.matches(text).into_iter().map(|i| self.set_indices[i]).collect(),
+            None => Vec::new(),
+        };
+        let unsorted = !found.is_empty() && !self.others.is_empty();
+        for (i, engine) in &self.others {
+            if engine.is_search_at(text, 0)? {
+                found.push(*i);
+            }
+        }
+        if unsorted {
+            found.sort_unstable();
+        }
+        Ok(found)
+    }
+
+    fn is_search(&self, text: &[u8]) -> Result<bool, AppError> {
+        let in_set = match &self.set {
+            Some(SetImpl::Str(set)) => set.is_match(crate::as_str(text)),
+            Some(SetImpl::Bytes(set)) => set.is_match(text),
+            None => false,
+        };
+        if in_set {
+            return Ok(true);
+        }
+        for (_, engine) in &self.others {
+            if engine.is_search_at(text, 0)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// The lowest index of a pattern matching somewhere in `text`.
+    fn first_match(&self, text: &[u8]) -> Result<Option<usize>, AppError> {
+        let first_in_set = match &self.set {
+            Some(SetImpl::Str(set)) => set.matches(crate::as_str(text)).into_iter().next(),
+            Some(SetImpl::Bytes(set)) => set.matches(text).into_iter().next(),
+            None => None,
+        }.map(|i| self.set_indices[i]);
+        // `others` is in pattern order, so the first hit below the set's wins.
+        for (i, engine) in self.others.iter().take_while(|(i, _)| first_in_set.is_none_or(|first| *i < first)) {
+            if engine.is_search_at(text, 0)? {
+                return Ok(Some(*i));
+            }
+        }
+        Ok(first_in_set)
+    }
+}
+
+/// Several patterns tested against a text in one pass, like `regex::RegexSet`.
+#[pyclass(frozen)]
+pub struct PatternSet {
+    engines: Arc<SetEngines>,
+}
+
+impl PatternSet {
+    fn run<T: Send>(&self, text: &Bound<'_, PyAny>, f: impl FnOnce(&[u8]) -> Result<T, AppError> + Send) -> PyResult<T> {
+        let subject = Subject::for_pattern(text, self.engines.bytes)?;
+        let text = subject.text()?;
+        Ok(subject.detach(|| f(text.as_bytes()))?)
+    }
+}
+
+#[pymethods]
+impl PatternSet {
+    #[new]
+    #[pyo3(signature = (patterns, config=None))]
+    fn new(patterns: &Bound<'_, PyAny>, config: Option<ConfigArg>) -> PyResult<Self> {
+        let mut sources = Vec::new();
+        let mut bytes = None;
+        for pattern in patterns.try_iter()? {
+            let pattern = pattern?;
+            let (source, is_bytes) = pattern_source(&pattern)?;
+            if *bytes.get_or_insert(is_bytes) != is_bytes {
+                return Err(PyTypeError::new_err("cannot mix str and bytes patterns in a PatternSet"));
+            }
+            sources.push(source.into_owned());
+        }
+        let bytes = bytes.unwrap_or(false);
+        let key = (sources, bytes, ConfigArg::resolve(config, bytes)?);
+        if let Some(engines) = SET_CACHE.get(&key) {
+            return Ok(PatternSet { engines });
+        }
+        let engines = Arc::new(SetEngines::new(&key.0, key.2.as_ref(), key.1)?);
+        SET_CACHE.insert(key, engines.clone(), engines.memory_usage());
+        Ok(PatternSet { engines })
+    }
+
+    fn __len__(&self) -> usize {
+        self.engines.len
+    }
+
+    /// Names the engines backing the set, e.g. "regex_set+pcre2".
+    pub fn engine_info(&self) -> String {
+        let mut names: Vec<String> = Vec::new();
+        if self.engines.set.is_some() {
+            names.push("regex_set".to_string());
+        }
+        for (_, engine) in &self.engines.others {
+            let name = engine.engine_info();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names.join("+")
+    }
+
+    pub fn matches(&self, text: &Bound<'_, PyAny>) -> PyResult<Vec<usize>> {
+        self.run(text, |text| self.engines.matches(text))
+    }
+
+    pub fn is_search(&self, text: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.run(text, |text| self.engines.is_search(text))
+    }
+
+    pub fn first_match(&self, text: &Bound<'_, PyAny>) -> PyResult<Option<usize>> {
+        self.run(text, |text| self.engines.first_match(text))
+    }
+
+    /// `matches` over every subject of `texts`, run in parallel.
+    #[pyo3(signature = (texts, n_threads=None))]
+    pub fn matches_many(&self, py: Python, texts: &Bound<'_, PyAny>, n_threads: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        let subjects = texts.try_iter()?
+            .map(|text| Subject::for_pattern(&text?, self.engines.bytes))
+            .collect::<PyResult<Vec<_>>>()?;
+        map_many(py, &subjects, n_threads, |text: Text| self.engines.matches(text.as_bytes()))
+    }
+}
@@ -0,0 +1,1278 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use pyo3::prelude::*;
+use regex_syntax::hir::{Hir, HirKind};
+
+use crate::{syntax_parser, ReConfig};
+use crate::analysis::Features;
+use crate::unicode;
+use crate::exceptions::ReError;
+
+/// The syntax a pattern is written in.
+#[pyclass]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Syntax {
+    /// Python's `re` syntax, translated for each engine.
+    Python = 0,
+    /// Handed to the engines as written.
+    Native = 1,
+}
+
+/// The pattern syntax of one of the engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Regex,
+    Pcre2,
+    Fancy,
+}
+
+/// Where a match has to sit, see `build_engines`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Anywhere at or after the start offset.
+    Search,
+    /// Exactly at the start offset.
+    Start,
+    /// From the start offset to the end of the haystack.
+    Full,
+    /// Exactly at the start offset, and not empty; see `ReEngine::next_match`.
+    NonEmpty,
+}
+
+// Python's inline flags.
+const IGNORECASE: u8 = 1;
+const LOCALE: u8 = 2;
+const MULTILINE: u8 = 4;
+const DOTALL: u8 = 8;
+const UNICODE: u8 = 16;
+const VERBOSE: u8 = 32;
+const ASCII: u8 = 64;
+const TYPE_FLAGS: u8 = ASCII | LOCALE | UNICODE;
+
+fn flag(c: char) -> Option<u8> {
+    match c {
+        'i' => Some(IGNORECASE),
+        'L' => Some(LOCALE),
+        'm' => Some(MULTILINE),
+        's' => Some(DOTALL),
+        'u' => Some(UNICODE),
+        'x' => Some(VERBOSE),
+        'a' => Some(ASCII),
+        _ => None,
+    }
+}
+
+/// `sre` refuses repeat counts from this value on.
+const MAXREPEAT: u64 = u32::MAX as u64;
+/// `sre` refuses group numbers from this value on.
+const MAXGROUPS: usize = i32::MAX as usize / 2;
+
+/// `(min, max)` length of what a node matches; `None` is unbounded.
+pub type Width = (usize, Option<usize>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Perl {
+    Digit,
+    Word,
+    Space,
+}
+
+impl Perl {
+    /// The class under `re.ASCII`, as ranges.
+    fn ascii_ranges(self) -> &'static [(char, char)] {
+        match self {
+            Perl::Digit => &[('0', '9')],
+            Perl::Word => &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+            Perl::Space => &[('\t', '\r'), (' ', ' ')],
+        }
+    }
+
+    /// The class in a Unicode `str` pattern, as ranges.
+    fn unicode_ranges(self) -> &'static [(char, char)] {
+        match self {
+            Perl::Digit => unicode::DIGIT,
+            Perl::Word => unicode::WORD,
+            Perl::Space => unicode::SPACE,
+        }
+    }
+
+    fn escape(self, negated: bool) -> &'static str {
+        match (self, negated) {
+            (Perl::Digit, false) => "\\d",
+            (Perl::Digit, true) => "\\D",
+            (Perl::Word, false) => "\\w",
+            (Perl::Word, true) => "\\W",
+            (Perl::Space, false) => "\\s",
+            (Perl::Space, true) => "\\S",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Perl { class: Perl, negated: bool },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Group {
+    Capture(Option<String>),
+    /// `(?:...)`, switching case-insensitivity when it carries an `i` flag.
+    NonCapture { ignore_case: Option<bool> },
+    LookAhead { negated: bool },
+    LookBehind { negated: bool },
+    Atomic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Greed {
+    Greedy,
+    Lazy,
+    Possessive,
+}
+
+/// A parsed Python pattern. Flags are resolved while parsing, so every node
+/// carries the meaning it has at its place in the pattern.
+#[derive(Debug, Clone)]
+pub enum Node {
+    Empty,
+    Literal(char),
+    Any { dot_all: bool },
+    Perl { class: Perl, negated: bool, ascii: bool },
+    Class { negated: bool, items: Vec<ClassItem>, ascii: bool },
+    /// `^`
+    Start { multiline: bool },
+    /// `$`
+    End { multiline: bool },
+    /// `\A`
+    StartText,
+    /// `\Z`
+    EndText,
+    WordBoundary { negated: bool, ascii: bool },
+    Group { kind: Group, body: Box<Node> },
+    Repeat { body: Box<Node>, min: u32, max: Option<u32>, greed: Greed },
+    Backref(usize),
+    Conditional { group: usize, yes: Box<Node>, no: Option<Box<Node>> },
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+}
+
+impl Node {
+    fn is_anchor(&self) -> bool {
+        matches!(self, Node::Start { .. } | Node::End { .. } | Node::StartText | Node::EndText | Node::WordBoundary { .. })
+    }
+
+    fn is_repeat(&self) -> bool {
+        matches!(self, Node::Repeat { .. })
+    }
+
+    /// The nodes before a `$` ending the pattern outside any group, if it does.
+    pub fn before_final_end(&self) -> Option<&[Node]> {
+        match self {
+            Node::End { multiline: false } => Some(&[]),
+            Node::Concat(nodes) => match nodes.split_last() {
+                Some((Node::End { multiline: false }, rest)) => Some(rest),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Mirrors `sre_parse.SubPattern.getwidth`; `groups` holds the width of
+    /// every closed group.
+    fn width(&self, groups: &[Option<Width>]) -> Width {
+        let add = |(lo, hi): Width, (l, h): Width| (lo + l, hi.zip(h).map(|(hi, h)| hi + h));
+        match self {
+            Node::Literal(_) | Node::Any { .. } | Node::Perl { .. } | Node::Class { .. } => (1, Some(1)),
+            Node::Empty | Node::Start { .. } | Node::End { .. } | Node::StartText | Node::EndText
+                | Node::WordBoundary { .. } => (0, Some(0)),
+            Node::Group { kind: Group::LookAhead { .. } | Group::LookBehind { .. }, .. } => (0, Some(0)),
+            Node::Group { body, .. } => body.width(groups),
+            Node::Repeat { body, min, max, .. } => {
+                let (lo, hi) = body.width(groups);
+                let hi = match (hi, max) {
+                    (Some(0), _) => Some(0),
+                    (Some(hi), Some(max)) => Some(hi.saturating_mul(*max as usize)),
+                    _ => None,
+                };
+                (lo.saturating_mul(*min as usize), hi)
+            },
+            Node::Backref(group) => groups.get(group - 1).copied().flatten().unwrap_or((0, None)),
+            Node::Conditional { yes, no, .. } => {
+                let (lo, hi) = yes.width(groups);
+                match no {
+                    Some(no) => {
+                        let (l, h) = no.width(groups);
+                        (lo.min(l), hi.zip(h).map(|(hi, h)| hi.max(h)))
+                    },
+                    None => (0, hi),
+                }
+            },
+            Node::Concat(nodes) => nodes.iter().fold((0, Some(0)), |width, node| add(width, node.width(groups))),
+            Node::Alternation(branches) => {
+                let widths: Vec<Width> = branches.iter().map(|branch| branch.width(groups)).collect();
+                let lo = widths.iter().map(|w| w.0).min().unwrap_or(0);
+                let hi = widths.iter().try_fold(0, |hi, w| w.1.map(|h| hi.max(h)));
+                (lo, hi)
+            },
+        }
+    }
+
+    /// Pushes the capturing groups opened in this node to `nesting`, each
+    /// holding the number of the last group nested in it.
+    fn nest(&self, nesting: &mut Vec<usize>) {
+        match self {
+            Node::Group { kind, body } => {
+                let index = nesting.len();
+                let capture = matches!(kind, Group::Capture(_));
+                if capture {
+                    nesting.push(index);
+                }
+                body.nest(nesting);
+                if capture {
+                    nesting[index] = nesting.len() - 1;
+                }
+            },
+            Node::Repeat { body, .. } => body.nest(nesting),
+            Node::Conditional { yes, no, .. } => {
+                yes.nest(nesting);
+                if let Some(no) = no {
+                    no.nest(nesting);
+                }
+            },
+            Node::Concat(nodes) | Node::Alternation(nodes) => nodes.iter().for_each(|node| node.nest(nesting)),
+            _ => {},
+        }
+    }
+}
+
+/// `Node::nest` for a pattern the `regex` crate parsed.
+fn nest_hir(hir: &Hir, nesting: &mut Vec<usize>) {
+    match hir.kind() {
+        HirKind::Capture(capture) => {
+            let index = nesting.len();
+            nesting.push(index);
+            nest_hir(&capture.sub, nesting);
+            nesting[index] = nesting.len() - 1;
+        },
+        HirKind::Repetition(repetition) => nest_hir(&repetition.sub, nesting),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().for_each(|hir| nest_hir(hir, nesting)),
+        _ => {},
+    }
+}
+
+/// A Python pattern parsed once, ready to be spelled in any engine's dialect.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pattern: String,
+    root: Node,
+    bytes: bool,
+    /// Case-insensitive as a whole, through the config or a global `(?i)`.
+    ignore_case: bool,
+    /// `\r`, `\n` and `\r\n` all end a line.
+    crlf: bool,
+    features: Features,
+    /// Shortest and longest match, the latter `None` when unbounded.
+    width: Width,
+}
+
+struct Parser<'p> {
+    pattern: &'p str,
+    chars: Vec<char>,
+    pos: usize,
+    bytes: bool,
+    /// Width of every group by number, `None` while it is still open.
+    groups: Vec<Option<Width>>,
+    names: HashMap<String, usize>,
+    /// Number of groups opened before the outermost look-behind being parsed.
+    lookbehind_groups: Option<usize>,
+    /// Groups named by conditionals, which may come later in the pattern.
+    condition_refs: Vec<(usize, usize)>,
+    /// Makes plain repeats lazy and `?` ones greedy, like the `regex` crate's flag.
+    swap_greed: bool,
+}
+
+impl<'p> Parser<'p> {
+    fn error(&self, message: impl Into<String>, pos: usize) -> ReError {
+        ReError::at(message, self.pattern, Some(pos))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Up to `n` characters matching `accept`.
+    fn take_while(&mut self, n: usize, accept: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while taken.len() < n {
+            match self.peek() {
+                Some(c) if accept(c) => {
+                    taken.push(c);
+                    self.pos += 1;
+                },
+                _ => break,
+            }
+        }
+        taken
+    }
+
+    /// Reads a name up to `terminator`, like `Tokenizer.getuntil`.
+    fn name_until(&mut self, terminator: char, what: &str) -> Result<String, ReError> {
+        let start = self.pos;
+        let mut name = String::new();
+        loop {
+            match self.next() {
+                None if name.is_empty() => return Err(self.error(format!("missing {}", what), start)),
+                None => return Err(self.error(format!("missing {}, unterminated name", terminator), start)),
+                Some(c) if c == terminator => {
+                    if name.is_empty() {
+                        return Err(self.error(format!("missing {}", what), start));
+                    }
+                    return Ok(name);
+                },
+                Some(c) => name.push(c),
+            }
+        }
+    }
+
+    fn check_group_name(&self, name: &str, pos: usize) -> Result<(), ReError> {
+        if !crate::template::is_identifier(name) || (self.bytes && !name.is_ascii()) {
+            return Err(self.error(format!("bad character in group name '{}'", name), pos));
+        }
+        Ok(())
+    }
+
+    fn is_closed(&self, group: usize) -> bool {
+        self.groups.get(group - 1).is_some_and(|width| width.is_some())
+    }
+
+    /// A reference to `group` from the current position.
+    fn check_reference(&self, group: usize, pos: usize) -> Result<(), ReError> {
+        if !self.is_closed(group) {
+            return Err(self.error("cannot refer to an open group", pos));
+        }
+        if self.lookbehind_groups.is_some_and(|opened| group > opened) {
+            return Err(self.error("cannot refer to group defined in the same lookbehind subpattern", pos));
+        }
+        Ok(())
+    }
+
+    /// Branches separated by `|`, up to a `)` or the end of the pattern.
+    /// Global flags can only open the first branch of the whole pattern.
+    fn alternation(&mut self, flags: &mut u8, top: bool) -> Result<Node, ReError> {
+        let mut branches = vec![self.sequence(flags, top)?];
+        while self.eat('|') {
+            branches.push(self.sequence(flags, false)?);
+        }
+        Ok(match branches.len() {
+            1 => branches.pop().unwrap(),
+            _ => Node::Alternation(branches),
+        })
+    }
+
+    fn sequence(&mut self, flags: &mut u8, first: bool) -> Result<Node, ReError> {
+        let mut items: Vec<Node> = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let start = self.pos;
+            self.pos += 1;
+            if *flags & VERBOSE != 0 {
+                if matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0b' | '\x0c') {
+                    continue;
+                }
+                if c == '#' {
+                    while self.next().is_some_and(|c| c != '\n') {}
+                    continue;
+                }
+            }
+            match c {
+                '\\' => items.push(self.escape(*flags, start)?),
+                '[' => items.push(self.class(*flags, start)?),
+                '.' => items.push(Node::Any { dot_all: *flags & DOTALL != 0 }),
+                '^' => items.push(Node::Start { multiline: *flags & MULTILINE != 0 }),
+                '$' => items.push(Node::End { multiline: *flags & MULTILINE != 0 }),
+                '(' => {
+                    let global_allowed = first && items.is_empty();
+                    if let Some(node) = self.group(flags, start, global_allowed)? {
+                        items.push(node);
+                    }
+                },
+                '*' | '+' | '?' | '{' => {
+                    let (min, max) = match c {
+                        '*' => (0, None),
+                        '+' => (1, None),
+                        '?' => (0, Some(1)),
+                        _ => match self.braces()? {
+                            Some(bounds) => bounds,
+                            None => {
+                                items.push(Node::Literal('{'));
+                                continue;
+                            },
+                        },
+                    };
+                    let body = match items.pop() {
+                        Some(item) if item.is_repeat() => return Err(self.error("multiple repeat", start)),
+                        Some(item) if !item.is_anchor() => item,
+                        _ => return Err(self.error("nothing to repeat", start)),
+                    };
+                    let greed = if self.eat('?') {
+                        Greed::Lazy
+                    } else if self.eat('+') {
+                        Greed::Possessive
+                    } else {
+                        Greed::Greedy
+                    };
+                    let greed = match (greed, self.swap_greed) {
+                        (Greed::Greedy, true) => Greed::Lazy,
+                        (Greed::Lazy, true) => Greed::Greedy,
+                        (greed, _) => greed,
+                    };
+                    items.push(Node::Repeat { body: Box::new(body), min, max, greed });
+                },
+                c => items.push(Node::Literal(c)),
+            }
+        }
+        Ok(match items.len() {
+            0 => Node::Empty,
+            1 => items.pop().unwrap(),
+            _ => Node::Concat(items),
+        })
+    }
+
+    /// The bounds of a `{m,n}` repeat, or `None` when the brace is a literal.
+    fn braces(&mut self) -> Result<Option<(u32, Option<u32>)>, ReError> {
+        let here = self.pos;
+        if self.peek() == Some('}') {
+            return Ok(None);
+        }
+        let lo = self.take_while(usize::MAX, |c| c.is_ascii_digit());
+        let hi = match self.eat(',') {
+            true => self.take_while(usize::MAX, |c| c.is_ascii_digit()),
+            false => lo.clone(),
+        };
+        if !self.eat('}') {
+            self.pos = here;
+            return Ok(None);
+        }
+        let bound = |digits: &str| match digits.parse::<u64>() {
+            Ok(n) if n < MAXREPEAT => Ok(n as u32),
+            _ => Err(self.error("the repetition number is too large", here)),
+        };
+        let min = match lo.is_empty() {
+            true => 0,
+            false => bound(&lo)?,
+        };
+        let max = match hi.is_empty() {
+            true => None,
+            false => Some(bound(&hi)?),
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("min repeat greater than max repeat", here));
+        }
+        Ok(Some((min, max)))
+    }
+
+    /// A backslash escape outside a class; the backslash is at `start`.
+    fn escape(&mut self, flags: u8, start: usize) -> Result<Node, ReError> {
+        let Some(c) = self.next() else {
+            return Err(self.error("bad escape (end of pattern)", start));
+        };
+        let ascii = flags & ASCII != 0;
+        Ok(match c {
+            'A' => Node::StartText,
+            'Z' => Node::EndText,
+            'b' | 'B' => Node::WordBoundary { negated: c == 'B', ascii },
+            'd' | 'D' => Node::Perl { class: Perl::Digit, negated: c == 'D', ascii },
+            'w' | 'W' => Node::Perl { class: Perl::Word, negated: c == 'W', ascii },
+            's' | 'S' => Node::Perl { class: Perl::Space, negated: c == 'S', ascii },
+            '1'..='9' => {
+                // An octal escape takes three octal digits; anything else is
+                // a group reference.
+                let mut digits = String::from(c);
+                if let Some(d) = self.peek().filter(|d| d.is_ascii_digit()) {
+                    self.pos += 1;
+                    digits.push(d);
+                    let is_octal = |c: char| ('0'..='7').contains(&c);
+                    if is_octal(c) && is_octal(d) && self.peek().is_some_and(is_octal) {
+                        digits.push(self.next().unwrap());
+                        return self.octal(&digits, start).map(Node::Literal);
+                    }
+                }
+                let group: usize = digits.parse().unwrap();
+                if group > self.groups.len() {
+                    return Err(self.error(format!("invalid group reference {}", group), start + 1));
+                }
+                self.check_reference(group, start)?;
+                Node::Backref(group)
+            },
+            c => Node::Literal(self.char_escape(c, start, false)?),
+        })
+    }
+
+    fn octal(&self, digits: &str, start: usize) -> Result<char, ReError> {
+        let value = u32::from_str_radix(digits, 8).unwrap();
+        if value > 0o377 {
+            return Err(self.error(format!("octal escape value \\{} outside of range 0-0o377", digits), start));
+        }
+        Ok(char::from_u32(value).unwrap())
+    }
+
+    /// An escape standing for one character; `c` follows the backslash at `start`.
+    fn char_escape(&mut self, c: char, start: usize, in_class: bool) -> Result<char, ReError> {
+        let hex = |parser: &mut Self, c: char, n: usize| {
+            let digits = parser.take_while(n, |c| c.is_ascii_hexdigit());
+            if digits.len() != n {
+                return Err(parser.error(format!("incomplete escape \\{}{}", c, digits), start));
+            }
+            char::from_u32(u32::from_str_radix(&digits, 16).unwrap())
+                .ok_or_else(|| parser.error(format!("bad escape \\{}{}", c, digits), start))
+        };
+        Ok(match c {
+            'a' => '\x07',
+            'b' if in_class => '\x08',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            'x' => hex(self, c, 2)?,
+            'u' if !self.bytes => hex(self, c, 4)?,
+            'U' if !self.bytes => hex(self, c, 8)?,
+            'N' if !self.bytes => {
+                return Err(self.error("named Unicode escapes (\\N{...}) are not supported", start));
+            },
+            '0' => {
+                let digits = format!("0{}", self.take_while(2, |c| ('0'..='7').contains(&c)));
+                self.octal(&digits, start)?
+            },
+            '1'..='7' if in_class => {
+                let digits = format!("{}{}", c, self.take_while(2, |c| ('0'..='7').contains(&c)));
+                self.octal(&digits, start)?
+            },
+            c if c.is_ascii_alphanumeric() => return Err(self.error(format!("bad escape \\{}", c), start)),
+            c => c,
+        })
+    }
+
+    /// A class item read from an escape: a character, or a Perl class.
+    fn class_escape(&mut self, start: usize) -> Result<ClassItem, ReError> {
+        let Some(c) = self.next() else {
+            return Err(self.error("bad escape (end of pattern)", start));
+        };
+        Ok(match c {
+            'd' | 'D' => ClassItem::Perl { class: Perl::Digit, negated: c == 'D' },
+            'w' | 'W' => ClassItem::Perl { class: Perl::Word, negated: c == 'W' },
+            's' | 'S' => ClassItem::Perl { class: Perl::Space, negated: c == 'S' },
+            c => ClassItem::Char(self.char_escape(c, start, true)?),
+        })
+    }
+
+    /// A `[...]` set; the bracket is at `start`.
+    fn class(&mut self, flags: u8, start: usize) -> Result<Node, ReError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        let unterminated = |parser: &Self| parser.error("unterminated character set", start);
+        loop {
+            let here = self.pos;
+            let item = match self.next() {
+                None => return Err(unterminated(self)),
+                Some(']') if !items.is_empty() => break,
+                Some('\\') => self.class_escape(here)?,
+                Some(c) => ClassItem::Char(c),
+            };
+            if !self.eat('-') {
+                items.push(item);
+                continue;
+            }
+            let there = self.pos;
+            let end = match self.next() {
+                None => return Err(unterminated(self)),
+                Some(']') => {
+                    items.push(item);
+                    items.push(ClassItem::Char('-'));
+                    break;
+                },
+                Some('\\') => self.class_escape(there)?,
+                Some(c) => ClassItem::Char(c),
+            };
+            match (item, end) {
+                (ClassItem::Char(lo), ClassItem::Char(hi)) if lo <= hi => items.push(ClassItem::Range(lo, hi)),
+                _ => {
+                    let range: String = self.chars[here..self.pos].iter().collect();
+                    return Err(self.error(format!("bad character range {}", range), here));
+                },
+            }
+        }
+        Ok(Node::Class { negated, items, ascii: flags & ASCII != 0 })
+    }
+
+    /// What follows a `(` at `start`: a group, or `None` for a comment or
+    /// global flags, which `global_allowed` says may appear here.
+    fn group(&mut self, flags: &mut u8, start: usize, global_allowed: bool) -> Result<Option<Node>, ReError> {
+        let unterminated = |parser: &Self| parser.error("missing ), unterminated subpattern", start);
+        let mut sub_flags = *flags;
+        let kind = if !self.eat('?') {
+            Group::Capture(None)
+        } else {
+            let Some(c) = self.next() else {
+                return Err(self.error("unexpected end of pattern", self.pos));
+            };
+            match c {
+                'P' => {
+                    let at = self.pos;
+                    if self.eat('<') {
+                        let name = self.name_until('>', "group name")?;
+                        self.check_group_name(&name, at + 1)?;
+                        Group::Capture(Some(name))
+                    } else if self.eat('=') {
+                        let name = self.name_until(')', "group name")?;
+                        self.check_group_name(&name, at + 1)?;
+                        let Some(&group) = self.names.get(&name) else {
+                            return Err(self.error(format!("unknown group name '{}'", name), at + 1));
+                        };
+                        self.check_reference(group, at + 1)?;
+                        return Ok(Some(Node::Backref(group)));
+                    } else if self.peek() == Some('>') {
+                        return Err(self.error("group recursion (?P>name) is not supported", start + 1));
+                    } else {
+                        return Err(match self.next() {
+                            None => self.error("unexpected end of pattern", self.pos),
+                            Some(c) => self.error(format!("unknown extension ?P{}", c), start + 1),
+                        });
+                    }
+                },
+                ':' => Group::NonCapture { ignore_case: None },
+                '#' => {
+                    loop {
+                        match self.next() {
+                            None => return Err(self.error("missing ), unterminated comment", start)),
+                            Some(')') => return Ok(None),
+                            Some(_) => {},
+                        }
+                    }
+                },
+                '=' => Group::LookAhead { negated: false },
+                '!' => Group::LookAhead { negated: true },
+                '<' => match self.next() {
+                    Some('=') => Group::LookBehind { negated: false },
+                    Some('!') => Group::LookBehind { negated: true },
+                    None => return Err(self.error("unexpected end of pattern", self.pos)),
+                    Some(c) => return Err(self.error(format!("unknown extension ?<{}", c), start + 1)),
+                },
+                '(' => return self.conditional(flags, start).map(Some),
+                '>' => Group::Atomic,
+                c if c == '-' || flag(c).is_some() => match self.flags(c)? {
+                    (on, None) => {
+                        if !global_allowed {
+                            return Err(self.error("global flags not at the start of the expression", start));
+                        }
+                        if on & LOCALE != 0 {
+                            return Err(self.error("the LOCALE flag is not supported", start));
+                        }
+                        *flags |= on;
+                        return Ok(None);
+                    },
+                    (on, Some(off)) => {
+                        sub_flags = (sub_flags | on) & !off;
+                        if on & ASCII != 0 {
+                            sub_flags &= !UNICODE;
+                        } else if on & UNICODE != 0 {
+                            sub_flags &= !ASCII;
+                        }
+                        let ignore_case = match (on & IGNORECASE != 0, off & IGNORECASE != 0) {
+                            (true, _) => Some(true),
+                            (_, true) => Some(false),
+                            _ => None,
+                        };
+                        Group::NonCapture { ignore_case }
+                    },
+                },
+                c => return Err(self.error(format!("unknown extension ?{}", c), start + 1)),
+            }
+        };
+
+        let capture = match &kind {
+            Group::Capture(name) => {
+                if let Some(name) = name {
+                    if let Some(&previous) = self.names.get(name) {
+                        return Err(self.error(format!(
+                            "redefinition of group name '{}' as group {}; was group {}",
+                            name, self.groups.len() + 1, previous,
+                        ), start + 4));
+                    }
+                    self.names.insert(name.clone(), self.groups.len() + 1);
+                }
+                if self.groups.len() + 1 >= MAXGROUPS {
+                    return Err(self.error("too many groups", start));
+                }
+                self.groups.push(None);
+                Some(self.groups.len())
+            },
+            _ => None,
+        };
+        let lookbehind = matches!(kind, Group::LookBehind { .. });
+        let outer_lookbehind = lookbehind && self.lookbehind_groups.is_none();
+        if outer_lookbehind {
+            self.lookbehind_groups = Some(self.groups.len());
+        }
+        let body = self.alternation(&mut sub_flags, false)?;
+        if outer_lookbehind {
+            self.lookbehind_groups = None;
+        }
+        if !self.eat(')') {
+            return Err(unterminated(self));
+        }
+        let width = body.width(&self.groups);
+        if lookbehind && width.1 != Some(width.0) {
+            return Err(ReError::at("look-behind requires fixed-width pattern", self.pattern, None));
+        }
+        if let Some(group) = capture {
+            self.groups[group - 1] = Some(width);
+        }
+        Ok(Some(Node::Group { kind, body: Box::new(body) }))
+    }
+
+    /// Inline flags starting with `c`: those turned on, and for a scoped
+    /// `(?on-off:...)` group those turned off; `None` for global flags.
+    fn flags(&mut self, mut c: char) -> Result<(u8, Option<u8>), ReError> {
+        let mut on = 0;
+        let mut off = 0;
+        if c != '-' {
+            loop {
+                let f = flag(c).unwrap();
+                if !self.bytes && f == LOCALE {
+                    return Err(self.error("bad inline flags: cannot use 'L' flag with a str pattern", self.pos));
+                }
+                if self.bytes && f == UNICODE {
+                    return Err(self.error("bad inline flags: cannot use 'u' flag with a bytes pattern", self.pos));
+                }
+                on |= f;
+                if f & TYPE_FLAGS != 0 && on & TYPE_FLAGS != f {
+                    return Err(self.error("bad inline flags: flags 'a', 'u' and 'L' are incompatible", self.pos));
+                }
+                c = match self.next() {
+                    None => return Err(self.error("missing -, : or )", self.pos)),
+                    Some(c) if flag(c).is_some() || ")-:".contains(c) => c,
+                    Some(c) if c.is_alphabetic() => return Err(self.error("unknown flag", self.pos - 1)),
+                    Some(_) => return Err(self.error("missing -, : or )", self.pos - 1)),
+                };
+                if ")-:".contains(c) {
+                    break;
+                }
+            }
+        }
+        if c == ')' {
+            return Ok((on, None));
+        }
+        if c == '-' {
+            loop {
+                c = match self.next() {
+                    None if off == 0 => return Err(self.error("missing flag", self.pos)),
+                    None => return Err(self.error("missing :", self.pos)),
+                    Some(':') if off != 0 => break,
+                    Some(c) if flag(c).is_some() => c,
+                    Some(c) if c.is_alphabetic() => return Err(self.error("unknown flag", self.pos - 1)),
+                    Some(_) if off == 0 => return Err(self.error("missing flag", self.pos - 1)),
+                    Some(_) => return Err(self.error("missing :", self.pos - 1)),
+                };
+                let f = flag(c).unwrap();
+                if f & TYPE_FLAGS != 0 {
+                    return Err(self.error("bad inline flags: cannot turn off flags 'a', 'u' and 'L'", self.pos));
+                }
+                off |= f;
+            }
+        }
+        if on & off != 0 {
+            return Err(self.error("bad inline flags: flag turned on and off", self.pos - 1));
+        }
+        Ok((on, Some(off)))
+    }
+
+    /// `(?(group)yes|no)`; the `(?(` is already read.
+    fn conditional(&mut self, flags: &mut u8, start: usize) -> Result<Node, ReError> {
+        let at = self.pos;
+        let name = self.name_until(')', "group name")?;
+        let group = if crate::template::is_identifier(&name) {
+            self.check_group_name(&name, at)?;
+            match self.names.get(&name) {
+                Some(&group) => group,
+                None => return Err(self.error(format!("unknown group name '{}'", name), at)),
+            }
+        } else {
+            let group = match name.bytes().all(|b| b.is_ascii_digit()) {
+                true => name.parse::<usize>().unwrap_or(usize::MAX),
+                false => return Err(self.error(format!("bad character in group name '{}'", name), at)),
+            };
+            if group == 0 {
+                return Err(self.error("bad group number", at));
+            }
+            if group >= MAXGROUPS {
+                return Err(self.error(format!("invalid group reference {}", name), at));
+            }
+            self.condition_refs.push((group, at));
+            group
+        };
+        if self.lookbehind_groups.is_some() {
+            self.check_reference(group, at)?;
+        }
+        let yes = self.sequence(flags, false)?;
+        let no = match self.eat('|') {
+            true => {
+                let no = self.sequence(flags, false)?;
+                if self.peek() == Some('|') {
+                    return Err(self.error("conditional backref with more than two branches", self.pos));
+                }
+                Some(Box::new(no))
+            },
+            false => None,
+        };
+        if !self.eat(')') {
+            return Err(self.error("missing ), unterminated subpattern", start));
+        }
+        Ok(Node::Conditional { group, yes: Box::new(yes), no })
+    }
+}
+
+impl Parsed {
+    /// Parses `pattern` the way Python's `re` does, starting from the flags of
+    /// `config`. Errors carry `re`'s messages and positions; constructs no
+    /// engine can reproduce are refused here too.
+    pub fn new(pattern: &str, config: Option<&ReConfig>, bytes: bool) -> Result<Parsed, ReError> {
+        let mut parser = Parser {
+            pattern,
+            chars: pattern.chars().collect(),
+            pos: 0,
+            bytes,
+            groups: Vec::new(),
+            names: HashMap::new(),
+            lookbehind_groups: None,
+            condition_refs: Vec::new(),
+            swap_greed: config.is_some_and(|cfg| cfg.swap_greed),
+        };
+        let mut flags = 0;
+        if let Some(cfg) = config {
+            if cfg.case_insensitive { flags |= IGNORECASE; }
+            if cfg.multiline { flags |= MULTILINE; }
+            if cfg.ignore_whitespace { flags |= VERBOSE; }
+            if cfg.dotall { flags |= DOTALL; }
+            if cfg.ascii { flags |= ASCII; }
+        }
+        let root = parser.alternation(&mut flags, true)?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unbalanced parenthesis", parser.pos));
+        }
+        if flags & ASCII != 0 && flags & UNICODE != 0 {
+            return Err(ReError::at("ASCII and UNICODE flags are incompatible", pattern, None));
+        }
+        if let Some(&(group, pos)) = parser.condition_refs.iter().find(|(group, _)| *group > parser.groups.len()) {
+            return Err(parser.error(format!("invalid group reference {}", group), pos));
+        }
+        let crlf = config.is_some_and(|cfg| cfg.crlf);
+        let features = Features::of_python(&root, crlf, bytes);
+        let width = root.width(&parser.groups);
+        Ok(Parsed {
+            pattern: pattern.to_string(),
+            root,
+            bytes,
+            ignore_case: flags & IGNORECASE != 0,
+            crlf,
+            features,
+            width,
+        })
+    }
+
+    /// The nodes before a final `$` that `dialect` spells with a trailing group.
+    fn split_final_end(&self, dialect: Dialect) -> Option<&[Node]> {
+        match dialect {
+            Dialect::Regex if !self.crlf => self.root.before_final_end(),
+            _ => None,
+        }
+    }
+
+    /// Spells the pattern in `dialect`, placing matches as `anchor` says.
+    /// Fails for constructs `dialect` cannot express faithfully.
+    pub fn emit(&self, dialect: Dialect, anchor: Anchor) -> Result<String, ReError> {
+        if (anchor, dialect) == (Anchor::NonEmpty, Dialect::Regex) {
+            return Err(ReError::new("the regex crate cannot require a non-empty match"));
+        }
+        let mut emitter = Emitter { dialect, bytes: self.bytes, crlf: self.crlf, out: String::new() };
+        // Python matches Unicode text by default; `(*UCP)` must open the pattern.
+        if !self.bytes {
+            emitter.out.push_str(match dialect {
+                Dialect::Pcre2 => "(*UCP)",
+                Dialect::Regex | Dialect::Fancy => "(?u)",
+            });
+        }
+        emitter.out.push_str(match (anchor, dialect) {
+            (Anchor::Search, _) => "",
+            (Anchor::Start, Dialect::Regex) => "\\A(?:",
+            (Anchor::Full, Dialect::Regex) => "(?:",
+            (Anchor::Start | Anchor::Full | Anchor::NonEmpty, _) => "\\G(?:",
+        });
+        if self.ignore_case {
+            emitter.out.push_str("(?i)");
+        }
+        match self.split_final_end(dialect) {
+            // The `regex` crate lacks look-ahead, so the final `$` takes the
+            // newline it may stand before into a last group, and the engine's
+            // searches cut it off the match (see `EngineImpl::Std`). Before
+            // the `\z` of `Full` it can only match at the end anyway.
+            Some(rest) => {
+                rest.iter().try_for_each(|node| emitter.node(node)).map_err(|message| ReError::at(message, &self.pattern, None))?;
+                if anchor != Anchor::Full {
+                    emitter.out.push_str("(\\n?)\\z");
+                }
+            },
+            None => emitter.node(&self.root).map_err(|message| ReError::at(message, &self.pattern, None))?,
+        }
+        emitter.out.push_str(match anchor {
+            Anchor::Search => "",
+            Anchor::Start => ")",
+            Anchor::Full => ")\\z",
+            // `\G` still marks the start offset, so this rules out an empty match.
+            Anchor::NonEmpty => ")(?!\\G)",
+        });
+        Ok(emitter.out)
+    }
+}
+
+struct Emitter {
+    dialect: Dialect,
+    bytes: bool,
+    crlf: bool,
+    out: String,
+}
+
+/// Fails for what the `regex` crate cannot do.
+fn regex_lacks(dialect: Dialect, what: &str) -> Result<(), String> {
+    match dialect {
+        Dialect::Regex => Err(format!("{} not supported by the regex crate", what)),
+        _ => Ok(()),
+    }
+}
+
+impl Emitter {
+    fn char(&mut self, c: char, in_class: bool) {
+        let special = match in_class {
+            true => "\\[]^-&~#",
+            false => "\\.+*?()|[]{}^$#",
+        };
+        if special.contains(c) {
+            self.out.push('\\');
+            self.out.push(c);
+        } else if c.is_ascii_graphic() {
+            self.out.push(c);
+        } else if self.bytes {
+            // The braced form is a code point to the `regex` crate, even
+            // with Unicode mode off; `\xHH` is the raw byte.
+            write!(self.out, "\\x{:02X}", c as u32).unwrap();
+        } else {
+            write!(self.out, "\\x{{{:X}}}", c as u32).unwrap();
+        }
+    }
+
+    fn ranges(&mut self, ranges: &[(char, char)]) {
+        for &(lo, hi) in ranges {
+            self.char(lo, true);
+            if hi != lo {
+                self.out.push('-');
+                self.char(hi, true);
+            }
+        }
+    }
+
+    /// The code points outside `ranges`, which are sorted and disjoint.
+    fn complement(&mut self, ranges: &[(char, char)]) {
+        let mut next = Some('\0');
+        let mut gaps = Vec::new();
+        for &(lo, hi) in ranges {
+            if let Some(from) = next.filter(|&from| from < lo) {
+                // Surrogates are no `char`s: step over them both ways.
+                gaps.push((from, char::from_u32(lo as u32 - 1).unwrap_or('\u{D7FF}')));
+            }
+            next = (hi != char::MAX).then(|| char::from_u32(hi as u32 + 1).unwrap_or('\u{E000}'));
+        }
+        if let Some(from) = next {
+            gaps.push((from, char::MAX));
+        }
+        self.ranges(&gaps);
+    }
+
+    /// A Perl class in a text pattern, spelled out as the ranges Python gives
+    /// it, since no engine agrees with Python on all of Unicode; `bytes`
+    /// patterns are ASCII-only on every engine anyway.
+    fn perl(&mut self, class: Perl, negated: bool, ascii: bool, in_class: bool) {
+        if self.bytes {
+            self.out.push_str(class.escape(negated));
+            return;
+        }
+        let ranges = match ascii {
+            true => class.ascii_ranges(),
+            false => class.unicode_ranges(),
+        };
+        match (negated, in_class) {
+            (false, false) => {
+                self.out.push('[');
+                self.ranges(ranges);
+                self.out.push(']');
+            },
+            (true, false) => {
+                self.out.push_str("[^");
+                self.ranges(ranges);
+                self.out.push(']');
+            },
+            (false, true) => self.ranges(ranges),
+            (true, true) => self.complement(ranges),
+        }
+    }
+
+    /// Python's word boundary, from its word class: the `regex` crate's own
+    /// `\b` goes by another `\w`, so only its ASCII one will do.
+    fn word_boundary(&mut self, negated: bool, ascii: bool) -> Result<(), String> {
+        if self.bytes {
+            self.out.push_str(if negated { "\\B" } else { "\\b" });
+            return Ok(());
+        }
+        if self.dialect == Dialect::Regex {
+            if !ascii {
+                return Err("Unicode word boundaries are not supported by the regex crate".to_string());
+            }
+            self.out.push_str(if negated { "(?-u:\\B)" } else { "(?-u:\\b)" });
+            return Ok(());
+        }
+        let start = self.out.len();
+        self.perl(Perl::Word, false, ascii, false);
+        let w = self.out.split_off(start);
+        match negated {
+            false => write!(self.out, "(?:(?<={w})(?!{w})|(?<!{w})(?={w}))"),
+            true => write!(self.out, "(?:(?<={w})(?={w})|(?<!{w})(?!{w}))"),
+        }.unwrap();
+        Ok(())
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), String> {
+        match node {
+            Node::Empty => {},
+            Node::Literal(c) => self.char(*c, false),
+            Node::Any { dot_all: true } => self.out.push_str("(?s:.)"),
+            Node::Any { dot_all: false } if self.crlf => self.out.push_str("[^\\r\\n]"),
+            Node::Any { dot_all: false } => self.out.push_str("[^\\n]"),
+            Node::Perl { class, negated, ascii } => self.perl(*class, *negated, *ascii, false),
+            Node::Class { negated, items, ascii } => {
+                self.out.push_str(if *negated { "[^" } else { "[" });
+                for item in items {
+                    match item {
+                        ClassItem::Char(c) => self.char(*c, true),
+                        ClassItem::Range(lo, hi) => self.ranges(&[(*lo, *hi)]),
+                        ClassItem::Perl { class, negated } => self.perl(*class, *negated, *ascii, true),
+                    }
+                }
+                self.out.push(']');
+            },
+            Node::Start { multiline: false } | Node::StartText => self.out.push_str("\\A"),
+            // fancy-regex has no CRLF mode, and PCRE2's would also match
+            // between `\r` and `\n`.
+            Node::Start { multiline: true } if self.crlf => self.out.push_str(match self.dialect {
+                Dialect::Regex => "(?mR:^)",
+                Dialect::Pcre2 | Dialect::Fancy => "(?<![^\\r\\n])(?!(?<=\\r)\\n)",
+            }),
+            Node::End { multiline: true } if self.crlf => self.out.push_str(match self.dialect {
+                Dialect::Regex => "(?mR:$)",
+                Dialect::Pcre2 | Dialect::Fancy => "(?=[\\r\\n]|\\z)(?!(?<=\\r)\\n)",
+            }),
+            // PCRE2's multiline `^` does not match after a newline ending the text.
+            Node::Start { multiline: true } => self.out.push_str(match self.dialect {
+                Dialect::Pcre2 => "(?<![^\\n])",
+                Dialect::Regex | Dialect::Fancy => "(?m:^)",
+            }),
+            Node::End { multiline: true } => self.out.push_str("(?m:$)"),
+            // Python's `$` also matches before a newline ending the text.
+            Node::End { multiline: false } => match self.dialect {
+                Dialect::Regex => return Err("`$` before a final newline is not supported by the regex crate".to_string()),
+                _ if self.crlf => self.out.push_str("(?=\\r?\\n?\\z)(?!(?<=\\r)\\n)"),
+                Dialect::Pcre2 => self.out.push_str("(?-m:$)"),
+                Dialect::Fancy => self.out.push_str("(?=\\n?\\z)"),
+            },
+            Node::EndText => self.out.push_str("\\z"),
+            Node::WordBoundary { negated, ascii } => self.word_boundary(*negated, *ascii)?,
+            Node::Group { kind, body } => {
+                match kind {
+                    Group::Capture(None) => self.out.push('('),
+                    Group::Capture(Some(name)) => write!(self.out, "(?P<{}>", name).unwrap(),
+                    Group::NonCapture { ignore_case: None } => self.out.push_str("(?:"),
+                    Group::NonCapture { ignore_case: Some(true) } => self.out.push_str("(?i:"),
+                    Group::NonCapture { ignore_case: Some(false) } => self.out.push_str("(?-i:"),
+                    Group::LookAhead { negated } => {
+                        regex_lacks(self.dialect, "look-around is")?;
+                        self.out.push_str(if *negated { "(?!" } else { "(?=" });
+                    },
+                    Group::LookBehind { negated } => {
+                        regex_lacks(self.dialect, "look-around is")?;
+                        self.out.push_str(if *negated { "(?<!" } else { "(?<=" });
+                    },
+                    Group::Atomic => {
+                        regex_lacks(self.dialect, "atomic groups are")?;
+                        self.out.push_str("(?>");
+                    },
+                }
+                self.node(body)?;
+                self.out.push(')');
+            },
+            Node::Repeat { body, min, max, greed } => {
+                if *greed == Greed::Possessive {
+                    regex_lacks(self.dialect, "possessive repeats are")?;
+                    self.out.push_str("(?>");
+                }
+                self.node(body)?;
+                match (min, max) {
+                    (0, None) => self.out.push('*'),
+                    (1, None) => self.out.push('+'),
+                    (0, Some(1)) => self.out.push('?'),
+                    (min, None) => write!(self.out, "{{{},}}", min).unwrap(),
+                    (min, Some(max)) if min == max => write!(self.out, "{{{}}}", min).unwrap(),
+                    (min, Some(max)) => write!(self.out, "{{{},{}}}", min, max).unwrap(),
+                }
+                match greed {
+                    Greed::Greedy => {},
+                    Greed::Lazy => self.out.push('?'),
+                    Greed::Possessive => self.out.push(')'),
+                }
+            },
+            Node::Backref(group) => match self.dialect {
+                Dialect::Regex => return Err("backreferences are not supported by the regex crate".to_string()),
+                Dialect::Pcre2 => write!(self.out, "\\g{{{}}}", group).unwrap(),
+                Dialect::Fancy => write!(self.out, "\\k<{}>", group).unwrap(),
+            },
+            Node::Conditional { group, yes, no } => {
+                regex_lacks(self.dialect, "conditional groups are")?;
+                write!(self.out, "(?({})", group).unwrap();
+                self.node(yes)?;
+                if let Some(no) = no {
+                    self.out.push('|');
+                    self.node(no)?;
+                }
+                self.out.push(')');
+            },
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.node(node)?;
+                }
+            },
+            Node::Alternation(branches) => {
+                for (i, branch) in branches.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push('|');
+                    }
+                    self.node(branch)?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+/// A pattern as the user wrote it, to be spelled in each engine's dialect.
+pub enum Source<'p> {
+    Native(&'p str, Features),
+    Python(Parsed),
+}
+
+impl<'p> Source<'p> {
+    /// Reads `pattern` in the syntax `config` selects, Python's by default.
+    pub fn new(pattern: &'p str, config: Option<&ReConfig>, bytes: bool) -> Result<Self, ReError> {
+        match config.map_or(Syntax::Python, |cfg| cfg.syntax) {
+            Syntax::Native => Ok(Source::Native(pattern, Features::of_native(pattern, config, bytes))),
+            Syntax::Python => Parsed::new(pattern, config, bytes).map(Source::Python),
+        }
+    }
+
+    /// The pattern as written.
+    pub fn raw(&self) -> &str {
+        match self {
+            Source::Native(pattern, _) => pattern,
+            Source::Python(parsed) => &parsed.pattern,
+        }
+    }
+
+    /// Whether the pattern in the `regex` crate's dialect, anchored as `anchor`
+    /// says, ends in a group holding the newline its final `$` matched before.
+    pub fn final_newline_group(&self, anchor: Anchor) -> bool {
+        match self {
+            Source::Native(..) => false,
+            Source::Python(parsed) => anchor != Anchor::Full && parsed.split_final_end(Dialect::Regex).is_some(),
+        }
+    }
+
+    /// Shortest and longest match of a Python pattern; unknown for a native one.
+    pub fn width(&self) -> Option<Width> {
+        match self {
+            Source::Native(..) => None,
+            Source::Python(parsed) => Some(parsed.width),
+        }
+    }
+
+    /// For every group by number, the number of the last group nested in it,
+    /// or itself; group 0 holds them all. Empty when a native pattern is not
+    /// one the `regex` crate parses.
+    pub fn nesting(&self, config: Option<&ReConfig>, bytes: bool) -> Vec<usize> {
+        let mut nesting = vec![0];
+        match self {
+            Source::Python(parsed) => parsed.root.nest(&mut nesting),
+            Source::Native(pattern, _) => match syntax_parser(config, bytes).parse(pattern) {
+                Ok(hir) => nest_hir(&hir, &mut nesting),
+                Err(_) => return Vec::new(),
+            },
+        }
+        nesting[0] = nesting.len() - 1;
+        nesting
+    }
+
+    /// What in the pattern calls for a backtracking engine.
+    pub fn features(&self) -> &Features {
+        match self {
+            Source::Native(_, features) => features,
+            Source::Python(parsed) => &parsed.features,
+        }
+    }
+
+    /// The pattern in `dialect`, anchored as `anchor` says.
+    ///
+    /// `\G` pins a match to the search's start offset, so `match(text, pos)` can
+    /// run anchored; the `regex` crate lacks it and anchors at the text start or,
+    /// for `fullmatch`, only at the end (see `build_engines` and `ReEngine::anchored`).
+    /// `\z` rather than `$`: it stays an end-of-text anchor under multiline mode.
+    pub fn dialect(&self, dialect: Dialect, anchor: Anchor) -> Result<Cow<'_, str>, ReError> {
+        let pattern = match self {
+            Source::Python(parsed) => return parsed.emit(dialect, anchor).map(Cow::Owned),
+            Source::Native(pattern, _) => *pattern,
+        };
+        Ok(match (anchor, dialect) {
+            (Anchor::Search, _) => Cow::Borrowed(pattern),
+            (Anchor::Start, Dialect::Regex) => Cow::Owned(format!("\\A(?:{})", pattern)),
+            (Anchor::Start, _) => Cow::Owned(format!("\\G(?:{})", pattern)),
+            (Anchor::Full, Dialect::Regex) => Cow::Owned(format!("(?:{})\\z", pattern)),
+            (Anchor::Full, _) => Cow::Owned(format!("\\G(?:{})\\z", pattern)),
+            (Anchor::NonEmpty, Dialect::Regex) => return Err(ReError::new("the regex crate cannot require a non-empty match")),
+            (Anchor::NonEmpty, _) => Cow::Owned(format!("\\G(?:{})(?!\\G)", pattern)),
+        })
+    }
+}
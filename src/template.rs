@@ -0,0 +1,238 @@
+use dashmap::DashMap;
+
+use crate::SpanVec;
+use crate::exceptions::{AppError, ReError};
+
+/// Python's `sre` refuses group numbers at or above this value.
+const MAX_GROUPS: usize = i32::MAX as usize / 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(Vec<u8>),
+    Group(usize),
+}
+
+/// A parsed replacement string, ready to be expanded once per match.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+/// Encodes a parsed literal; templates of `bytes` patterns are parsed from
+/// their Latin-1 decoding, like `re` does, and encoded back the same way.
+fn encode(literal: String, latin1: bool) -> Vec<u8> {
+    if latin1 {
+        literal.chars().map(|c| c as u8).collect()
+    } else {
+        literal.into_bytes()
+    }
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_alphabetic() => chars.all(|c| c == '_' || c.is_alphanumeric()),
+        _ => false,
+    }
+}
+
+impl Template {
+    /// Parses a replacement written in Python's `re.sub` syntax: `\1`, `\g<1>`,
+    /// `\g<name>`, octal escapes and the usual `\n`/`\t`-style character escapes.
+    ///
+    /// Errors follow the rules of `re`: unknown ASCII-letter escapes and references
+    /// to groups the pattern does not define are rejected up front. With `latin1`
+    /// the literals are encoded back to the bytes `repl` was decoded from.
+    pub fn parse_python(repl: &str, groups: usize, group_map: &DashMap<String, usize>, latin1: bool) -> Result<Template, AppError> {
+        let chars: Vec<char> = repl.chars().collect();
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+
+        // `pos` counts characters of `repl`, like `re.error.pos`.
+        let invalid = |message: String, pos: usize| AppError::InvalidPattern(ReError::at(message, repl, Some(pos)));
+        let push_group = |literal: &mut String, pieces: &mut Vec<Piece>, index: usize, pos: usize| {
+            if index > groups {
+                return Err(invalid(format!("invalid group reference {}", index), pos));
+            }
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(encode(std::mem::take(literal), latin1)));
+            }
+            pieces.push(Piece::Group(index));
+            Ok(())
+        };
+
+        while i < chars.len() {
+            let c = chars[i];
+            if c != '\\' {
+                literal.push(c);
+                i += 1;
+                continue;
+            }
+            let escape_pos = i;
+            let Some(&next) = chars.get(i + 1) else {
+                return Err(invalid("bad escape (end of pattern)".to_string(), escape_pos));
+            };
+            i += 2;
+            match next {
+                'g' => {
+                    if chars.get(i) != Some(&'<') {
+                        return Err(invalid("missing <".to_string(), i));
+                    }
+                    i += 1;
+                    let name_pos = i;
+                    let close = chars[i..].iter().position(|&c| c == '>');
+                    let name: String = match close {
+                        Some(0) => return Err(invalid("missing group name".to_string(), name_pos)),
+                        Some(len) => chars[i..i + len].iter().collect(),
+                        None if i == chars.len() => return Err(invalid("missing group name".to_string(), name_pos)),
+                        None => return Err(invalid("missing >, unterminated name".to_string(), name_pos)),
+                    };
+                    i += name.chars().count() + 1;
+                    let index = if !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()) {
+                        match name.parse::<usize>() {
+                            Ok(index) if index < MAX_GROUPS => index,
+                            _ => return Err(invalid(format!("invalid group reference {}", name), name_pos)),
+                        }
+                    } else {
+                        if !is_identifier(&name) {
+                            return Err(invalid(format!("bad character in group name '{}'", name), name_pos));
+                        }
+                        match group_map.get(&name) {
+                            Some(index) => *index,
+                            None => return Err(AppError::IndexOutOfBounds(ReError::new(format!("unknown group name '{}'", name)))),
+                        }
+                    };
+                    push_group(&mut literal, &mut pieces, index, name_pos)?;
+                },
+                '0' => {
+                    let mut value = 0u32;
+                    let mut taken = 0;
+                    while taken < 2 && chars.get(i).is_some_and(|c| ('0'..='7').contains(c)) {
+                        value = value * 8 + chars[i].to_digit(8).unwrap();
+                        i += 1;
+                        taken += 1;
+                    }
+                    literal.push(char::from_u32(value & 0xff).unwrap());
+                },
+                '1'..='9' => {
+                    let digits_pos = escape_pos + 1;
+                    let mut digits = String::from(next);
+                    if let Some(&d) = chars.get(i).filter(|c| c.is_ascii_digit()) {
+                        digits.push(d);
+                        i += 1;
+                        let is_octal = |c: char| ('0'..='7').contains(&c);
+                        if is_octal(next) && is_octal(d) && chars.get(i).is_some_and(|&c| is_octal(c)) {
+                            digits.push(chars[i]);
+                            i += 1;
+                            let value = u32::from_str_radix(&digits, 8).unwrap();
+                            if value > 0o377 {
+                                return Err(invalid(
+                                    format!("octal escape value \\{} outside of range 0-0o377", digits),
+                                    escape_pos,
+                                ));
+                            }
+                            literal.push(char::from_u32(value).unwrap());
+                            continue;
+                        }
+                    }
+                    push_group(&mut literal, &mut pieces, digits.parse().unwrap(), digits_pos)?;
+                },
+                'a' => literal.push('\x07'),
+                'b' => literal.push('\x08'),
+                'f' => literal.push('\x0c'),
+                'n' => literal.push('\n'),
+                'r' => literal.push('\r'),
+                't' => literal.push('\t'),
+                'v' => literal.push('\x0b'),
+                '\\' => literal.push('\\'),
+                c if c.is_ascii_alphabetic() => {
+                    return Err(invalid(format!("bad escape \\{}", c), escape_pos));
+                },
+                c => {
+                    literal.push('\\');
+                    literal.push(c);
+                },
+            }
+        }
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(encode(literal, latin1)));
+        }
+        Ok(Template { pieces })
+    }
+
+    /// Parses a replacement written in the `regex` crate's syntax: `$1`, `$name`,
+    /// `${name}` and `$$` for a literal dollar sign.
+    ///
+    /// Mirrors `regex::Captures::expand`: the longest run of `[_0-9A-Za-z]` after
+    /// `$` names the group, and references to unknown groups expand to nothing.
+    pub fn parse_rust(repl: &str, group_map: &DashMap<String, usize>, latin1: bool) -> Template {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut rest = repl;
+
+        while let Some(i) = rest.find('$') {
+            literal.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                literal.push('$');
+                rest = after;
+                continue;
+            }
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let end = rest.find(|c: char| !(c == '_' || c.is_ascii_alphanumeric())).unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                },
+            };
+            if name.is_empty() {
+                literal.push('$');
+                continue;
+            }
+            rest = after;
+            let index = match name.parse::<usize>() {
+                Ok(index) => Some(index),
+                Err(_) => group_map.get(name).map(|index| *index),
+            };
+            if let Some(index) = index {
+                if !literal.is_empty() {
+                    pieces.push(Piece::Literal(encode(std::mem::take(&mut literal), latin1)));
+                }
+                pieces.push(Piece::Group(index));
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(encode(literal, latin1)));
+        }
+        Template { pieces }
+    }
+
+    /// Returns the replacement text when the template references no groups.
+    pub fn literal(&self) -> Option<&[u8]> {
+        match self.pieces.as_slice() {
+            [] => Some(b""),
+            [Piece::Literal(s)] => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Appends the replacement for one match to `dst`; groups that did not
+    /// participate expand to the empty string.
+    pub fn expand(&self, text: &[u8], spans: &SpanVec, dst: &mut Vec<u8>) {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(s) => dst.extend_from_slice(s),
+                Piece::Group(i) => {
+                    if let Some(Some((s, e))) = spans.get(*i) {
+                        dst.extend_from_slice(&text[*s..*e]);
+                    }
+                },
+            }
+        }
+    }
+}
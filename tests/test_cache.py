@@ -0,0 +1,49 @@
+import unittest
+
+import reru
+
+
+class CacheTest(unittest.TestCase):
+    def setUp(self):
+        self.size = reru.get_cache_size()
+        reru.purge()
+
+    def tearDown(self):
+        reru.set_cache_size(self.size)
+        reru.purge()
+
+    def test_capacity_evicts(self):
+        reru.set_cache_size(2)
+        for pattern in ("a+", "b+", "a+", "c+", "b+"):
+            reru.compile(pattern)
+        info = reru.cache_info()
+        # "c+" evicts "b+", the least recently used, and "b+" then evicts "a+".
+        self.assertEqual((info.hits, info.misses, info.evictions), (1, 4, 2))
+        self.assertEqual((info.currsize, info.maxsize), (2, 2))
+        self.assertGreater(info.memory, 0)
+
+    def test_shrinking_evicts(self):
+        for pattern in ("a+", "b+", "c+"):
+            reru.compile(pattern)
+        reru.set_cache_size(1)
+        info = reru.cache_info()
+        self.assertEqual((info.evictions, info.currsize), (2, 1))
+
+    def test_purge_resets_counters(self):
+        reru.compile("a+")
+        reru.compile("a+")
+        reru.purge()
+        info = reru.cache_info()
+        self.assertEqual((info.hits, info.misses, info.evictions, info.currsize, info.memory), (0, 0, 0, 0, 0))
+
+    def test_uncached(self):
+        reru.compile("a+", cache=False)
+        self.assertEqual(reru.cache_info().misses, 0)
+        reru.set_cache_size(0)
+        reru.compile("b+")
+        info = reru.cache_info()
+        self.assertEqual((info.misses, info.currsize), (1, 0))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,41 @@
+import re
+import unittest
+
+import reru
+
+
+class CompileErrorTest(unittest.TestCase):
+    INVALID = ["(a|b", "a)", "a{2,1}", "\\", "(?<=a+)b", "(?P<1>a)", "[b-a]"]
+
+    def test_report_matches_re(self):
+        for pattern in self.INVALID:
+            with self.subTest(pattern=pattern):
+                with self.assertRaises(re.error) as expected:
+                    re.compile(pattern)
+                with self.assertRaises(reru.error) as raised:
+                    reru.compile(pattern)
+                self.assertEqual(raised.exception.msg, expected.exception.msg)
+                self.assertEqual(raised.exception.pos, expected.exception.pos)
+                self.assertEqual(str(raised.exception), str(expected.exception))
+
+    def test_explain_invalid(self):
+        verdicts = reru.explain_compile("(a|b")
+        self.assertEqual(verdicts[0], ("python_syntax", False, "missing ), unterminated subpattern at position 0"))
+        self.assertEqual([name for name, _, _ in verdicts[1:]], ["aho_corasick", "regex", "pcre2", "fancy_regex"])
+        for name, accepted, reason in verdicts[1:]:
+            self.assertFalse(accepted)
+            self.assertTrue(reason.startswith("not tried"), reason)
+
+    def test_explain_valid(self):
+        self.assertEqual(reru.explain_compile(r"(?<=a)b"), [
+            ("aho_corasick", False, "not an alternation of plain literals"),
+            ("regex", False, "look-around is not supported by the regex crate"),
+            ("pcre2", True, "compiled"),
+            ("fancy_regex", True, "compiled"),
+        ])
+        verdicts = dict((name, accepted) for name, accepted, _ in reru.explain_compile(b"foo|bar"))
+        self.assertEqual(verdicts, {"aho_corasick": True, "regex": True, "pcre2": True, "fancy_regex": False})
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,86 @@
+import threading
+import time
+import unittest
+
+import reru
+
+
+class GilReleaseTest(unittest.TestCase):
+    def setUp(self):
+        self.threshold = reru.get_gil_release_threshold()
+
+    def tearDown(self):
+        reru.set_gil_release_threshold(self.threshold)
+
+    def slow_scan(self):
+        """A pattern and a subject it takes a noticeable time to search."""
+        pattern = reru.compile(r"(?:[a-c][0-9]){8}[x-z]")
+        text = "a1b2c3" * 100_000
+        for _ in range(12):
+            start = time.perf_counter()
+            pattern.is_search(text)
+            if time.perf_counter() - start >= 0.05:
+                break
+            text *= 2
+        return pattern, text
+
+    def runs_during(self, scan):
+        """Whether another Python thread gets to run in the middle of `scan`."""
+        stamps = []
+        stop = threading.Event()
+
+        def ticker():
+            while not stop.is_set():
+                stamps.append(time.perf_counter())
+                time.sleep(0.001)
+
+        thread = threading.Thread(target=ticker)
+        thread.start()
+        try:
+            time.sleep(0.01)
+            start = time.perf_counter()
+            scan()
+            elapsed = time.perf_counter() - start
+        finally:
+            stop.set()
+            thread.join()
+        # A thread blocked on the GIL may still slip in right before or after
+        # the scan, so only its middle half counts.
+        low, high = start + elapsed / 4, start + elapsed * 3 / 4
+        return any(low <= stamp <= high for stamp in stamps)
+
+    def test_threads_progress_during_long_scan(self):
+        pattern, text = self.slow_scan()
+        reru.set_gil_release_threshold(1024)
+        self.assertTrue(self.runs_during(lambda: pattern.is_search(text)))
+        self.assertTrue(self.runs_during(lambda: pattern.findall(text)))
+        self.assertTrue(self.runs_during(lambda: pattern.sub("", text)))
+
+    def test_short_subjects_keep_the_gil(self):
+        pattern, text = self.slow_scan()
+        reru.set_gil_release_threshold(len(text) + 1)
+        self.assertFalse(self.runs_during(lambda: pattern.is_search(text)))
+
+    def test_concurrent_scans_return_correct_results(self):
+        pattern = reru.compile(r"\d+")
+        text = "ab 12 cd 345 " * 50_000
+        reru.set_gil_release_threshold(0)
+        results = [None] * 4
+
+        def worker(i):
+            results[i] = pattern.findall(text)
+
+        threads = [threading.Thread(target=worker, args=(i,)) for i in range(len(results))]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        self.assertEqual(results, [["12", "345"] * 50_000] * len(results))
+
+    def test_threshold_round_trips(self):
+        reru.set_gil_release_threshold(12345)
+        self.assertEqual(reru.get_gil_release_threshold(), 12345)
+
+
+if __name__ == "__main__":
+    unittest.main()
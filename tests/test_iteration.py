@@ -0,0 +1,31 @@
+import unittest
+
+import reru
+
+
+class FindIterTest(unittest.TestCase):
+    def test_bytearray_shrunk_mid_iteration(self):
+        # The iterator must stop rather than search from beyond the end of
+        # the text.
+        b = bytearray(b"aaaaaaaa")
+        it = reru.finditer(rb"a", b)
+        next(it), next(it), next(it)
+        del b[1:]
+        self.assertEqual(list(it), [])
+
+        b = bytearray(b"aaaaaaaa")
+        it = reru.compile(rb"a").finditer(b, 0, 8)
+        next(it), next(it)
+        del b[1:]
+        self.assertEqual(list(it), [])
+
+    def test_bytearray_shrunk_after_empty_match(self):
+        b = bytearray(b"aaaa")
+        it = reru.compile(rb"").finditer(b)
+        next(it), next(it), next(it)
+        del b[1:]
+        self.assertEqual(list(it), [])
+
+
+if __name__ == "__main__":
+    unittest.main()
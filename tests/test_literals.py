@@ -0,0 +1,56 @@
+import re
+import unittest
+
+import reru
+
+
+def spans(matches):
+    return [m.span() for m in matches]
+
+
+class LiteralsTest(unittest.TestCase):
+    """Alternations of plain literals run on Aho-Corasick, which must keep the
+    results of `re`."""
+
+    def check(self, pattern, strings, flags=0):
+        config = reru.ReConfig(case_insensitive=bool(flags & re.IGNORECASE))
+        obj, expected = reru.compile(pattern, config), re.compile(pattern, flags)
+        for s in strings:
+            with self.subTest(pattern=pattern, string=s):
+                for method in ("search", "match", "fullmatch"):
+                    m, e = getattr(obj, method)(s), getattr(expected, method)(s)
+                    self.assertEqual(m and m.span(), e and e.span(), method)
+                self.assertEqual(spans(obj.finditer(s)), spans(expected.finditer(s)))
+        return obj
+
+    def test_overlapping_alternatives(self):
+        for pattern in ("a|ab", "ab|a", "abc|b|bcd", "b|abc"):
+            obj = self.check(pattern, ["ab", "a", "xab", "abab", "abcd", "b", ""])
+            self.assertEqual(obj.engine_info(), "aho_corasick")
+
+    def test_hex_escapes(self):
+        obj = self.check(r"\x41|\xe9t\xe9", ["A", "\xe9t\xe9", "xA\xe9t\xe9", "a"])
+        self.assertEqual(obj.engine_info(), "aho_corasick")
+        obj = self.check(rb"\xff|\x41b", [b"\xff", b"Ab", b"x\xffAb", b"\xc3\xbf"])
+        self.assertEqual(obj.engine_info(), "aho_corasick")
+
+    def test_ignore_case(self):
+        # `k` and `s` also fold to the Kelvin sign and the long s.
+        strings = ["OK", "ok", "oK", "YES", "yeſ", "no"]
+        self.check("ok|yes", strings, re.IGNORECASE)
+        obj = self.check("no|ye", strings, re.IGNORECASE)
+        self.assertEqual(obj.engine_info(), "aho_corasick")
+
+    def test_native_escapes(self):
+        # Native syntax gives `\<` a meaning, a word start; `\.` is a literal.
+        config = reru.ReConfig(syntax=reru.Syntax.Native)
+        obj = reru.compile(r"\<a|b", config)
+        self.assertNotEqual(obj.engine_info(), "aho_corasick")
+        self.assertEqual(spans(obj.finditer("<a ba a")), [(1, 2), (3, 4), (6, 7)])
+        obj = reru.compile(r"\.a|b", config)
+        self.assertEqual(obj.engine_info(), "aho_corasick")
+        self.assertEqual(spans(obj.finditer(".a ba a")), [(0, 2), (3, 4)])
+
+
+if __name__ == "__main__":
+    unittest.main()
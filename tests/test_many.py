@@ -0,0 +1,57 @@
+import os
+import re
+import unittest
+
+import reru
+
+
+class ManyTest(unittest.TestCase):
+    """The `*_many` methods give, in input order, what the single-text methods give."""
+
+    TEXTS = ["id=%d%s" % (i, " x" * (i % 7)) if i % 3 else "none" for i in range(200)]
+
+    def test_input_order(self):
+        obj, expected = reru.compile(r"id=(\d+)"), re.compile(r"id=(\d+)")
+        texts = self.TEXTS
+        self.assertEqual(obj.is_search_many(texts), [bool(expected.search(t)) for t in texts])
+        self.assertEqual([m and m.span() for m in obj.search_many(texts)], [m and m.span() for m in map(expected.search, texts)])
+        self.assertEqual(reru.compile(r"\d+").findall_many(texts), [re.findall(r"\d+", t) for t in texts])
+        self.assertEqual(obj.sub_many(r"<\1>", texts), [expected.sub(r"<\1>", t) for t in texts])
+        self.assertEqual(obj.sub_many(r"<\1>", texts, 1), [expected.sub(r"<\1>", t, 1) for t in texts])
+
+    def test_n_threads(self):
+        obj = reru.compile(r"\d+")
+        expected = [re.findall(r"\d+", t) for t in self.TEXTS]
+        # More threads than cores run on the shared pool.
+        for n_threads in (1, 2, 3, (os.cpu_count() or 1) + 5, 10_000):
+            with self.subTest(n_threads=n_threads):
+                self.assertEqual(obj.findall_many(self.TEXTS, n_threads=n_threads), expected)
+                self.assertEqual(obj.is_search_many(self.TEXTS, n_threads), [bool(found) for found in expected])
+
+    def test_bytes(self):
+        obj = reru.compile(rb"\d+")
+        texts = [b"a1", bytearray(b"22"), memoryview(b"b"), b""]
+        self.assertEqual(obj.findall_many(texts), [[b"1"], [b"22"], [], []])
+
+    def test_wrong_type(self):
+        obj = reru.compile(r"a")
+        with self.assertRaises(TypeError):
+            obj.is_search_many(["a", 3])
+        with self.assertRaises(TypeError):
+            obj.search_many(["a", b"a"])
+
+    def test_match_limit(self):
+        config = reru.ReConfig(backtrack_limit=10_000)
+        obj = reru.compile(r"(a|aa|a)+\1c", config)
+        texts = ["aac", "a" * 32 + "dc"]
+        with self.assertRaises(reru.MatchLimitError):
+            obj.search_many(texts)
+        with self.assertRaises(reru.MatchLimitError):
+            obj.findall_many(texts, n_threads=2)
+        config = reru.ReConfig(backtrack_limit=10_000, on_match_error=reru.MatchErrorPolicy.NoMatch)
+        obj = reru.compile(r"(a|aa|a)+\1c", config)
+        self.assertEqual(obj.is_search_many(texts), [True, False])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,67 @@
+import unittest
+
+import reru
+
+CATASTROPHIC = r"(?<=x)(a|aa|a)+c"
+TEXT = "x" + "a" * 32 + "dc"
+
+
+def compile(pattern, policy=None, engine=reru.SelectEngine.Pcre2, limit=10_000):
+    if policy is None:
+        config = reru.ReConfig(backtrack_limit=limit)
+    else:
+        config = reru.ReConfig(backtrack_limit=limit, on_match_error=policy)
+    return reru.compile_custom(pattern, config, engine, cache=False)
+
+
+class MatchLimitTest(unittest.TestCase):
+    def test_raise(self):
+        obj = compile(CATASTROPHIC)
+        self.assertTrue(issubclass(reru.MatchLimitError, RuntimeError))
+        calls = {
+            "search": obj.search, "is_search": obj.is_search, "findall": obj.findall, "split": obj.split,
+            "finditer": lambda s: list(obj.finditer(s)), "sub": lambda s: obj.sub("-", s),
+        }
+        for name, call in calls.items():
+            with self.subTest(method=name):
+                with self.assertRaises(reru.MatchLimitError):
+                    call(TEXT)
+        # Under the limit the search runs as usual.
+        self.assertEqual(obj.search("xaac").span(), (1, 4))
+
+    def test_raise_is_the_default(self):
+        obj = compile(CATASTROPHIC, reru.MatchErrorPolicy.Raise)
+        with self.assertRaises(reru.MatchLimitError):
+            obj.search(TEXT)
+
+    def test_no_match(self):
+        obj = compile(CATASTROPHIC, reru.MatchErrorPolicy.NoMatch)
+        self.assertIsNone(obj.search(TEXT))
+        self.assertFalse(obj.is_search(TEXT))
+        self.assertEqual(obj.findall(TEXT), [])
+        self.assertEqual(obj.sub("-", TEXT), TEXT)
+        self.assertEqual(obj.search("xaac").span(), (1, 4))
+
+    def test_fallback(self):
+        # fancy-regex hands the part after the look-behind to the `regex`
+        # crate, which never gives up, and finds there is no match.
+        obj = compile(CATASTROPHIC, reru.MatchErrorPolicy.Fallback)
+        self.assertEqual(obj.engine_info(), "pcre2")
+        self.assertIsNone(obj.search(TEXT))
+        self.assertEqual(obj.search("x" + "a" * 32 + "c").span(), (1, 34))
+
+    def test_fallback_fails_too(self):
+        # A backreference keeps fancy-regex backtracking, and it gives up too.
+        obj = compile(r"(a|aa|a)+\1c", reru.MatchErrorPolicy.Fallback, reru.SelectEngine.Fancy)
+        with self.assertRaises(reru.MatchLimitError):
+            obj.search("a" * 32 + "dc")
+
+    def test_fallback_without_another_engine(self):
+        # fancy-regex cannot search bytes.
+        obj = compile(CATASTROPHIC.encode(), reru.MatchErrorPolicy.Fallback)
+        with self.assertRaises(reru.MatchLimitError):
+            obj.search(TEXT.encode())
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,211 @@
+import re
+import unittest
+
+import reru
+
+try:
+    from test.re_tests import tests, FAIL, SUCCEED, SYNTAX_ERROR
+except ImportError:
+    tests = None
+
+
+@unittest.skipIf(tests is None, "CPython's test.re_tests is not installed")
+class ReTestsTest(unittest.TestCase):
+    """Runs the corpus of CPython's `test_re.test_re_tests` against reru."""
+
+    def cases(self):
+        for t in tests:
+            if len(t) == 5:
+                pattern, s, outcome, repl, expected = t
+            else:
+                (pattern, s, outcome), repl, expected = t, None, None
+            yield pattern, s, outcome, repl, expected
+
+    def check(self, obj, pattern, s, outcome, repl, expected):
+        result = obj.search(s)
+        if outcome == FAIL:
+            self.assertIsNone(result)
+            return
+        self.assertIsNotNone(result)
+        vardict = {"found": result.group(0), "groups": result.group()}
+        for i in range(1, 100):
+            try:
+                gi = result.group(i)
+                if gi is None:
+                    gi = "None"
+            except IndexError:
+                gi = "Error"
+            vardict["g%d" % i] = gi
+        for name in obj.group_names():
+            gi = result.group(name)
+            vardict[name] = "None" if gi is None else gi
+        self.assertEqual(eval(repl, vardict), expected)
+        # The match must still be found when the search is limited to it.
+        start, end = result.span()
+        if pattern[:2] != r"\B" and pattern[-2:] != r"\B":
+            self.assertIsNotNone(obj.search(s, start, end + 1))
+
+    def test_syntax_errors(self):
+        for pattern, s, outcome, repl, expected in self.cases():
+            if outcome != SYNTAX_ERROR:
+                continue
+            with self.subTest(pattern=pattern):
+                with self.assertRaises(reru.error):
+                    reru.compile(pattern)
+
+    def test_search(self):
+        for pattern, s, outcome, repl, expected in self.cases():
+            if outcome == SYNTAX_ERROR:
+                continue
+            with self.subTest(pattern=pattern, string=s):
+                self.check(reru.compile(pattern), pattern, s, outcome, repl, expected)
+
+    def test_each_engine(self):
+        # An engine may decline a construct it cannot express, but whatever it
+        # compiles must behave like `re`.
+        for engine in (reru.SelectEngine.Std, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy):
+            for pattern, s, outcome, repl, expected in self.cases():
+                if outcome == SYNTAX_ERROR:
+                    continue
+                try:
+                    obj = reru.compile_custom(pattern, None, engine)
+                except reru.error:
+                    continue
+                with self.subTest(engine=engine, pattern=pattern, string=s):
+                    self.check(obj, pattern, s, outcome, repl, expected)
+
+    def test_ignore_case(self):
+        config = reru.ReConfig(case_insensitive=True)
+        for pattern, s, outcome, repl, expected in self.cases():
+            if outcome != SUCCEED:
+                continue
+            with self.subTest(pattern=pattern, string=s):
+                self.assertIsNotNone(reru.compile(pattern, config).search(s))
+
+    def test_bytes(self):
+        for pattern, s, outcome, repl, expected in self.cases():
+            if outcome != SUCCEED:
+                continue
+            try:
+                bpattern, bs = pattern.encode("ascii"), s.encode("ascii")
+            except UnicodeEncodeError:
+                continue
+            with self.subTest(pattern=bpattern, string=bs):
+                self.assertIsNotNone(reru.compile(bpattern).search(bs))
+
+
+class EmptyMatchTest(unittest.TestCase):
+    """An empty match may be followed by a non-empty one at the same position,
+    as in `re` since Python 3.7."""
+
+    CASES = [(r"|a", "a"), (r"\b|a", "a"), (r"(?=a)|a", "a"), (r"|a", "baa"), (r"x*", "axb")]
+
+    def test_findall_split_and_sub(self):
+        engines = (None, reru.SelectEngine.Std, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy)
+        for engine in engines:
+            for pattern, s in self.CASES:
+                try:
+                    obj = reru.compile_custom(pattern, None, engine)
+                except reru.error:
+                    continue
+                expected = re.compile(pattern)
+                with self.subTest(engine=engine, pattern=pattern, string=s):
+                    self.assertEqual(obj.findall(s), expected.findall(s))
+                    self.assertEqual(obj.split(s), expected.split(s))
+                    self.assertEqual(obj.sub("-", s), expected.sub("-", s))
+                    self.assertEqual([m.span() for m in obj.finditer(s)], [m.span() for m in expected.finditer(s)])
+
+
+class LastIndexTest(unittest.TestCase):
+    CASES = [
+        (r"(a)(b*)", "a"), (r"(x)?(a)()", "a"), (r"(a())", "a"), (r"((a)b)", "ab"),
+        (r"(?P<x>a)(?P<y>b*)", "a"), (r"(())", ""), (r"()()", ""),
+    ]
+
+    def test_lastindex_and_lastgroup(self):
+        engines = (None, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy)
+        for engine in engines:
+            for pattern, s in self.CASES:
+                m, expected = reru.compile_custom(pattern, None, engine).match(s), re.match(pattern, s)
+                with self.subTest(engine=engine, pattern=pattern, string=s):
+                    self.assertEqual(m.lastindex, expected.lastindex)
+                    self.assertEqual(m.lastgroup, expected.lastgroup)
+
+    def test_groups(self):
+        for engine in (None, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy):
+            for pattern, s in self.CASES:
+                m, expected = reru.compile_custom(pattern, None, engine).match(s), re.match(pattern, s)
+                with self.subTest(engine=engine, pattern=pattern, string=s):
+                    self.assertEqual(m.groups(), expected.groups())
+                    self.assertEqual(m.groups("-"), expected.groups("-"))
+                    self.assertIsInstance(m.groups(), tuple)
+
+    def test_negative_group(self):
+        m = reru.match(r"(a)", "a")
+        for method in (m.group, m.span, m.start, m.end, m.__getitem__):
+            with self.subTest(method=method.__name__):
+                with self.assertRaisesRegex(IndexError, "no such group"):
+                    method(-1)
+
+
+class UnicodeClassTest(unittest.TestCase):
+    """`\\d`, `\\s`, `\\w` and `\\b` follow Python's idea of the classes, not the
+    engine's."""
+
+    CASES = [
+        (r"\w+", "x\u00b2y"), (r"\w", "\u0301"), (r"\W", "\u0301"), (r"\d+", "\u0661\u0662\u00b3"),
+        (r"\s", "\u180e"), (r"\s", "\x1c"), (r"[\W\d]+", "a-\u0663b"), (r"[^\w]", "\u00e9\u00b2-"),
+        (r"\bx", "\u0301x"), (r"\bfoo\b", "\u00e9t\u00e9 foo"), (r"\B.", "\u00e9a "), (r"x\b", "x\u00b2"),
+        (r"\b\d", "\u0661"), (r"(?a)\w+\b", "a\u00e9"),
+    ]
+
+    ENGINES = (None, reru.SelectEngine.Std, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy)
+
+    def test_cases(self):
+        for engine in self.ENGINES:
+            for pattern, s in self.CASES:
+                with self.subTest(engine=engine, pattern=pattern, string=s):
+                    # The `regex` crate only has ASCII word boundaries to offer.
+                    unicode_boundary = re.search(r"\\[bB]", pattern) and not pattern.startswith("(?a)")
+                    if engine == reru.SelectEngine.Std and unicode_boundary:
+                        with self.assertRaisesRegex(reru.error, "word boundaries"):
+                            reru.compile_custom(pattern, None, engine)
+                        continue
+                    obj = reru.compile_custom(pattern, None, engine)
+                    self.assertEqual(obj.findall(s), re.findall(pattern, s))
+
+    def test_basic_multilingual_plane(self):
+        chars = [chr(c) for c in range(0x10000) if not 0xD800 <= c <= 0xDFFF]
+        # In slices, as PCRE2 checks the whole text on every search of a `str`.
+        texts = ["".join(chars[i:i + 2048]) for i in range(0, len(chars), 2048)]
+        for engine in self.ENGINES:
+            for pattern in (r"\d", r"\s", r"\w", r"\W", r"[\S\d]", r"\b"):
+                if engine == reru.SelectEngine.Std and pattern == r"\b":
+                    continue
+                obj, expected = reru.compile_custom(pattern, None, engine), re.compile(pattern)
+                with self.subTest(engine=engine, pattern=pattern):
+                    for text in texts:
+                        self.assertEqual([m.span() for m in obj.finditer(text)], [m.span() for m in expected.finditer(text)])
+
+
+class WindowTest(unittest.TestCase):
+    """`pos` and `endpos` bound the search as in `re`, even past each other."""
+
+    PATTERNS = ["", "()", "a|", "(b)|", "b", "bc?", "(?=)"]
+    BOUNDS = [(0, 3), (1, 2), (2, 1), (3, 0), (5, 1), (-1, -5), (2, 2), (1, 10), (4, 5)]
+
+    def test_pos_and_endpos(self):
+        for pattern in self.PATTERNS:
+            obj, expected = reru.compile(pattern), re.compile(pattern)
+            for pos, endpos in self.BOUNDS:
+                with self.subTest(pattern=pattern, pos=pos, endpos=endpos):
+                    for method in ("match", "search", "fullmatch"):
+                        m, e = getattr(obj, method)("abc", pos, endpos), getattr(expected, method)("abc", pos, endpos)
+                        self.assertEqual(m and (m.span(), m.groups(), m.pos, m.endpos), e and (e.span(), e.groups(), e.pos, e.endpos), method)
+                    self.assertEqual(obj.is_match("abc", pos, endpos), bool(expected.match("abc", pos, endpos)))
+                    self.assertEqual(obj.findall("abc", pos, endpos), expected.findall("abc", pos, endpos))
+                    self.assertEqual([m.span() for m in obj.finditer("abc", pos, endpos)], [m.span() for m in expected.finditer("abc", pos, endpos)])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,97 @@
+import re
+import unittest
+
+import reru
+
+ENGINES = (None, reru.SelectEngine.Std, reru.SelectEngine.Pcre2, reru.SelectEngine.Fancy)
+
+
+class TemplateTest(unittest.TestCase):
+    """Replacement templates follow Python's syntax on every engine."""
+
+    CASES = [
+        (r"(?P<w>\w+) (\d)", r"\g<w>:\2:\g<0>:\g<2>", "ab 1 cd 2"),
+        (r"(?P<w>\w+) (\d)", r"\2\g<1>0", "ab 1"),
+        (r"(a)|b", r"[\1]", "ab"),
+        (r"a", r"\n\t\\\a\101\0", "a"),
+        (r"a", r"$1 ${x} \$", "a"),
+    ]
+
+    def test_expansion(self):
+        for engine in ENGINES:
+            for pattern, repl, s in self.CASES:
+                obj = reru.compile_custom(pattern, None, engine)
+                with self.subTest(engine=engine, pattern=pattern, repl=repl):
+                    self.assertEqual(obj.sub(repl, s), re.sub(pattern, repl, s))
+                    self.assertEqual(obj.subn(repl, s, 1), re.subn(pattern, repl, s, 1))
+
+    def test_bytes(self):
+        repl = rb"\g<x>" + b"\xff" + rb"\n"
+        self.assertEqual(reru.sub(rb"(?P<x>a)", repl, b"ba"), re.sub(rb"(?P<x>a)", repl, b"ba"))
+
+    def test_invalid(self):
+        for repl in (r"\3", r"\g<3>", r"\g<-1>", r"\g<>", r"\g<1", r"\g<a b>", r"\q", "\\", r"\g"):
+            with self.subTest(repl=repl):
+                with self.assertRaises(re.error) as expected:
+                    re.sub(r"(a)(b)", repl, "ab")
+                with self.assertRaises(reru.TemplateError) as raised:
+                    reru.sub(r"(a)(b)", repl, "ab")
+                self.assertIsInstance(raised.exception, reru.error)
+                self.assertEqual(str(raised.exception), str(expected.exception))
+
+    def test_unknown_group_name(self):
+        with self.assertRaisesRegex(IndexError, "unknown group name 'x'"):
+            reru.sub(r"(a)", r"\g<x>", "a")
+
+    def test_rust_syntax(self):
+        # PCRE2 used to copy the template in verbatim.
+        for engine in ENGINES:
+            obj = reru.compile_custom(r"(?P<w>a)(b)", None, engine)
+            with self.subTest(engine=engine):
+                self.assertEqual(obj.sub(r"$2${w}\1$$", "abab", rust_syntax=True), r"ba\1$ba\1$")
+                self.assertEqual(obj.sub_many(r"[$0]", ["ab", "xab"], rust_syntax=True), ["[ab]", "x[ab]"])
+
+    def test_look_behind_on_pcre2(self):
+        obj = reru.compile_custom(r"(?<=x)(?P<w>a)", None, reru.SelectEngine.Pcre2)
+        self.assertEqual(obj.sub(r"<\g<w>\1>", "xaa"), re.sub(r"(?<=x)(?P<w>a)", r"<\g<w>\1>", "xaa"))
+        self.assertEqual(obj.sub(r"<${w}$1>", "xaa", rust_syntax=True), "x<aa>a")
+
+
+class CallableTest(unittest.TestCase):
+    def test_match_object(self):
+        def repl(m):
+            return "%s%d:%s" % (m.group(0).upper(), m.start(), m.group("x") or "")
+
+        for engine in ENGINES:
+            obj = reru.compile_custom(r"(?P<x>a)|b|", None, engine)
+            with self.subTest(engine=engine):
+                self.assertEqual(obj.sub(repl, "abc"), re.sub(r"(?P<x>a)|b|", repl, "abc"))
+                self.assertEqual(obj.subn(repl, "abc", 2), re.subn(r"(?P<x>a)|b|", repl, "abc", 2))
+        self.assertEqual(reru.sub(rb"\d", lambda m: m.group() * 2, b"a1b2"), b"a11b22")
+        self.assertEqual(reru.compile(r"a").sub_many(lambda m: "x", ["a", "ba"]), ["x", "bx"])
+
+    def test_none_is_empty(self):
+        self.assertEqual(reru.sub(r"a", lambda m: None, "bab"), re.sub(r"a", lambda m: None, "bab"))
+
+    def test_callback_raises(self):
+        calls = []
+
+        def repl(m):
+            calls.append(m.start())
+            raise KeyError("stop")
+
+        with self.assertRaises(KeyError) as raised:
+            reru.sub(r"a", repl, "aaa")
+        self.assertEqual(raised.exception.args, ("stop",))
+        self.assertEqual(calls, [0])
+
+    def test_wrong_type(self):
+        for pattern, s, result in ((r"a", "a", 3), (r"a", "a", b"x"), (rb"a", b"a", "x"), (rb"a", b"a", 3)):
+            with self.subTest(pattern=pattern, result=result):
+                with self.assertRaisesRegex(TypeError, "expected (str instance|a bytes-like object), %s found" % type(result).__name__):
+                    reru.sub(pattern, lambda m: result, s)
+        self.assertEqual(reru.sub(rb"a", lambda m: bytearray(b"x"), b"ab"), b"xb")
+
+
+if __name__ == "__main__":
+    unittest.main()